@@ -0,0 +1,157 @@
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
+use crate::lexer::Span;
+
+// Deepest the parser and resolver let blocks and expressions nest, so walking the tree
+// can't overflow the stack
+pub(crate) const MAX_NESTING: usize = 100;
+
+// AST Node types
+
+/// An expression together with where it appeared in the source
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub(crate) fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+    Int(i64),
+    BigInt(BigInt),
+    Float(f64),
+    String(String),
+    // A string with embedded `{...}` expressions; the parts are joined using each value's Display
+    Interpolation(Vec<Expr>),
+    Bool(bool),
+    Nil,
+    Identifier(String),
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+    List(Vec<Expr>),
+    Map(Vec<(Expr, Expr)>),
+    Index {
+        target: Box<Expr>,
+        index: Box<Expr>,
+    },
+    Binary {
+        left: Box<Expr>,
+        op: BinaryOp,
+        right: Box<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Equals,
+    NotEquals,
+    Greater,
+    Less,
+    GreaterEq,
+    LessEq,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub(crate) fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Equals => "==",
+            BinaryOp::NotEquals => "!=",
+            BinaryOp::Greater => ">",
+            BinaryOp::Less => "<",
+            BinaryOp::GreaterEq => ">=",
+            BinaryOp::LessEq => "<=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum UnaryOp {
+    Not,
+    Minus,
+}
+
+/// A function parameter together with where it's named in the source
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub span: Span,
+}
+
+/// A statement together with where it appeared in the source
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub(crate) fn new(kind: StatementKind, span: Span) -> Self {
+        Statement { kind, span }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StatementKind {
+    VarDecl {
+        name: String,
+        value: Expr,
+    },
+    Assignment {
+        name: String,
+        value: Expr,
+    },
+    IndexAssign {
+        target: Expr,
+        index: Expr,
+        value: Expr,
+    },
+    Print(Expr),
+    Debug(Expr),
+    If {
+        condition: Expr,
+        then_body: Vec<Statement>,
+        else_body: Option<Vec<Statement>>,
+    },
+    While {
+        condition: Expr,
+        body: Vec<Statement>,
+    },
+    // The body is shared with the function value the declaration creates, so running it
+    // doesn't copy the code
+    Function {
+        name: String,
+        params: Vec<Param>,
+        body: Rc<[Statement]>,
+    },
+    Return(Option<Expr>),
+    Expression(Expr),
+    Break,
+    Continue,
+}
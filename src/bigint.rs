@@ -0,0 +1,384 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Integer of any size. Integers that fit in an i64 are kept as `Value::Int`;
+/// this holds the ones that grew past it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    // Base 2^32 digits, least significant first, with no trailing zeros
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn from_i64(n: i64) -> Self {
+        let abs = n.unsigned_abs();
+        BigInt::from_parts(n < 0, vec![abs as u32, (abs >> 32) as u32])
+    }
+    
+    // Trims leading zero digits; zero is never negative
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        BigInt { negative: negative && !magnitude.is_empty(), magnitude }
+    }
+    
+    /// Parses a decimal integer with an optional leading '-'
+    pub fn parse(s: &str) -> Option<Self> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut magnitude = Vec::new();
+        // Nine decimal digits at a time always fit in a u32
+        for chunk in digits.as_bytes().chunks(9) {
+            let value = chunk.iter().fold(0, |acc, b| acc * 10 + u32::from(b - b'0'));
+            mul_small_add(&mut magnitude, 10u32.pow(chunk.len() as u32), value);
+        }
+        Some(BigInt::from_parts(negative, magnitude))
+    }
+    
+    /// Parses unsigned digits in the given radix (2 to 36)
+    pub fn parse_radix(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut magnitude = Vec::new();
+        for ch in digits.chars() {
+            mul_small_add(&mut magnitude, radix, ch.to_digit(radix)?);
+        }
+        Some(BigInt::from_parts(false, magnitude))
+    }
+    
+    /// Whole part of a float, rounded toward zero; None for infinities and NaN
+    pub fn from_f64(n: f64) -> Option<Self> {
+        if !n.is_finite() {
+            return None;
+        }
+        let whole = n.trunc();
+        // Below 2^53 the whole part fits in an i64
+        if whole.abs() < 9007199254740992.0 {
+            return Some(BigInt::from_i64(whole as i64));
+        }
+        // Past it the float is its 53-bit mantissa shifted left by the exponent
+        let bits = whole.to_bits();
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let shift = ((bits >> 52) & 0x7ff) as usize - 1075;
+        let mut magnitude = vec![0; shift / 32];
+        let shifted = u128::from(mantissa) << (shift % 32);
+        magnitude.extend([shifted as u32, (shifted >> 32) as u32, (shifted >> 64) as u32]);
+        Some(BigInt::from_parts(whole < 0.0, magnitude))
+    }
+    
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let abs = self.magnitude.iter().rev().fold(0u64, |acc, &digit| (acc << 32) | u64::from(digit));
+        if self.negative {
+            0i64.checked_sub_unsigned(abs)
+        } else {
+            i64::try_from(abs).ok()
+        }
+    }
+    
+    // Nearest float; very large values become infinity
+    pub fn to_f64(&self) -> f64 {
+        let abs = self.magnitude.iter().rev().fold(0.0, |acc, &digit| acc * 4294967296.0 + f64::from(digit));
+        if self.negative { -abs } else { abs }
+    }
+    
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+    
+    pub(crate) fn neg(&self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+    
+    pub(crate) fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.magnitude, &other.magnitude));
+        }
+        // Opposite signs: the larger magnitude decides the sign
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitudes(&other.magnitude, &self.magnitude))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.magnitude, &other.magnitude)),
+        }
+    }
+    
+    pub(crate) fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+    
+    pub(crate) fn mul(&self, other: &BigInt) -> BigInt {
+        let (a, b) = (&self.magnitude, &other.magnitude);
+        let mut product = vec![0u32; a.len() + b.len()];
+        for (i, &x) in a.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &y) in b.iter().enumerate() {
+                let t = u64::from(product[i + j]) + u64::from(x) * u64::from(y) + carry;
+                product[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            product[i + b.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, product)
+    }
+    
+    /// Quotient and remainder rounded toward zero, like i64's `/` and `%`; None when dividing by zero
+    pub(crate) fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divide_magnitudes(&self.magnitude, &other.magnitude);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+    
+    // self / divisor as a float, where self is smaller than the divisor. Both are cut down
+    // to the divisor's top digits first, so numbers too big for floats still give a fraction.
+    pub(crate) fn fraction_of(&self, divisor: &BigInt) -> f64 {
+        let skip = divisor.magnitude.len().saturating_sub(3);
+        let top = |n: &BigInt| {
+            let digits = n.magnitude.get(skip..).unwrap_or_default();
+            BigInt::from_parts(n.negative, digits.to_vec()).to_f64()
+        };
+        top(self) / top(divisor)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off nine decimal digits at a time, least significant first
+        let mut chunks = Vec::new();
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let (quotient, remainder) = div_small(&rest, 1_000_000_000);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+// The helpers below work on magnitudes without trailing zeros and return them the same way
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &digit) in long.iter().enumerate() {
+        let t = u64::from(digit) + u64::from(short.get(i).copied().unwrap_or(0)) + carry;
+        sum.push(t as u32);
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+// a - b, where a >= b
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut t = i64::from(digit) - i64::from(b.get(i).copied().unwrap_or(0)) - borrow;
+        borrow = 0;
+        if t < 0 {
+            t += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(t as u32);
+    }
+    while difference.last() == Some(&0) {
+        difference.pop();
+    }
+    difference
+}
+
+// magnitude = magnitude * factor + addend
+fn mul_small_add(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = u64::from(addend);
+    for digit in magnitude.iter_mut() {
+        let t = u64::from(*digit) * u64::from(factor) + carry;
+        *digit = t as u32;
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+fn div_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, &digit) in a.iter().enumerate().rev() {
+        let t = (remainder << 32) | u64::from(digit);
+        quotient[i] = (t / u64::from(divisor)) as u32;
+        remainder = t % u64::from(divisor);
+    }
+    while quotient.last() == Some(&0) {
+        quotient.pop();
+    }
+    (quotient, remainder as u32)
+}
+
+fn divide_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitudes(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if let [divisor] = b {
+        let (quotient, remainder) = div_small(a, *divisor);
+        let remainder = if remainder == 0 { Vec::new() } else { vec![remainder] };
+        return (quotient, remainder);
+    }
+    // Schoolbook long division, one bit at a time
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for digit in remainder.iter_mut() {
+            let shifted_out = *digit >> 31;
+            *digit = (*digit << 1) | carry;
+            carry = shifted_out;
+        }
+        if carry > 0 {
+            remainder.push(carry);
+        }
+        if compare_magnitudes(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitudes(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    while quotient.last() == Some(&0) {
+        quotient.pop();
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn big(s: &str) -> BigInt {
+        BigInt::parse(s).unwrap()
+    }
+    
+    #[test]
+    fn parse_and_display_round_trip() {
+        for s in ["0", "7", "-7", "4294967296", "-18446744073709551616", "1000000000", "123456789012345678901234567890"] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(big("000123").to_string(), "123");
+        for s in ["", "-", "1.5", "+1", "1_000", " 1"] {
+            assert_eq!(BigInt::parse(s), None, "{:?}", s);
+        }
+        assert_eq!(BigInt::parse_radix("ff", 16), Some(big("255")));
+    }
+    
+    #[test]
+    fn mul() {
+        assert_eq!(big("4294967296").mul(&big("4294967296")), big("18446744073709551616"));
+        let product = big("-12345678901234567890").mul(&big("98765432109876543210"));
+        assert_eq!(product.to_string(), "-1219326311370217952237463801111263526900");
+        assert_eq!(big("-5").mul(&big("-5")), big("25"));
+        assert!(big("-5").mul(&big("0")).is_zero());
+        assert_eq!(big("-5").mul(&big("0")).to_string(), "0");
+    }
+    
+    #[test]
+    fn div_rem_rounds_toward_zero() {
+        let cases = [
+            ("7", "2", "3", "1"),
+            ("-7", "2", "-3", "-1"),
+            ("7", "-2", "-3", "1"),
+            ("-7", "-2", "3", "-1"),
+            ("1", "18446744073709551616", "0", "1"),
+            ("-100000000000000000000000000001", "10000000000000", "-10000000000000000", "-1"),
+            ("340282366920938463463374607431768211456", "-18446744073709551617", "-18446744073709551615", "1"),
+        ];
+        for (l, r, quotient, remainder) in cases {
+            let (q, m) = big(l).div_rem(&big(r)).unwrap();
+            assert_eq!((q, m), (big(quotient), big(remainder)), "{} / {}", l, r);
+        }
+        assert_eq!(big("5").div_rem(&big("0")), None);
+    }
+    
+    #[test]
+    fn to_i64_and_to_f64_at_the_boundaries() {
+        assert_eq!(big("9223372036854775807").to_i64(), Some(i64::MAX));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775808").to_i64(), Some(i64::MIN));
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+        assert_eq!(big("-18446744073709551616").to_i64(), None);
+        assert_eq!(BigInt::from_i64(i64::MIN).to_string(), "-9223372036854775808");
+        
+        assert_eq!(big("9007199254740993").to_f64(), 9007199254740992.0);
+        assert_eq!(big("-18446744073709551616").to_f64(), -18446744073709551616.0);
+        assert_eq!(BigInt::parse(&format!("1{}", "0".repeat(308))).unwrap().to_f64(), 1e308);
+        assert_eq!(BigInt::parse(&format!("1{}", "0".repeat(309))).unwrap().to_f64(), f64::INFINITY);
+    }
+    
+    #[test]
+    fn from_f64_truncates() {
+        assert_eq!(BigInt::from_f64(2.9), Some(big("2")));
+        assert_eq!(BigInt::from_f64(-2.9), Some(big("-2")));
+        assert_eq!(BigInt::from_f64(9007199254740992.0), Some(big("9007199254740992")));
+        assert_eq!(BigInt::from_f64(-18446744073709551616.0), Some(big("-18446744073709551616")));
+        assert_eq!(BigInt::from_f64(1e300).unwrap().to_f64(), 1e300);
+        assert_eq!(BigInt::from_f64(f64::NAN), None);
+    }
+    
+    #[test]
+    fn fraction_of_numbers_too_big_for_floats() {
+        let huge = BigInt::parse(&format!("1{}", "0".repeat(400))).unwrap();
+        assert_eq!(huge.fraction_of(&huge.mul(&big("4"))), 0.25);
+        assert_eq!(huge.neg().fraction_of(&huge.mul(&big("-8"))), 0.125);
+        assert_eq!(big("1").fraction_of(&big("-3")), -1.0 / 3.0);
+    }
+}
@@ -0,0 +1,449 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{BinaryOp, Expr, ExprKind, Param, Statement, StatementKind, UnaryOp};
+use crate::lexer::Span;
+use crate::table::Table;
+use crate::value::Value;
+
+// Bytecode instructions for the VM. Operands are indexes into the owning chunk, except
+// that globals and functions are numbered in the interpreter's tables.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Instruction {
+    Constant(usize),
+    Nil,
+    True,
+    False,
+    Pop,
+    DefineGlobal(usize),
+    GetGlobal(usize),
+    SetGlobal(usize),
+    GetLocal(usize),
+    SetLocal(usize),
+    // Joins the top values into one string, for interpolated strings
+    Concat(usize),
+    BuildList(usize),
+    BuildMap(usize),
+    Index,
+    SetIndex,
+    Binary(BinaryOp),
+    Negate,
+    Not,
+    Jump(usize),
+    JumpIfFalse(usize),
+    // Used by && and ||: jump keeping the deciding value, otherwise pop it
+    JumpIfFalseOrPop(usize),
+    JumpIfTrueOrPop(usize),
+    Call(usize, usize),
+    Return,
+    Print,
+    Debug,
+    // The function in the chunk, and its index in the function table
+    DefineFunction(usize, usize),
+}
+
+// A compiled block of code with the constants and functions it refers to
+#[derive(Debug, Default)]
+pub(crate) struct Chunk {
+    pub(crate) code: Vec<Instruction>,
+    // Source location of each instruction, for runtime errors
+    pub(crate) spans: Vec<Span>,
+    pub(crate) constants: Vec<Value>,
+    pub(crate) functions: Vec<Rc<FunctionProto>>,
+}
+
+impl Chunk {
+    fn emit(&mut self, instruction: Instruction, span: Span) -> usize {
+        self.code.push(instruction);
+        self.spans.push(span);
+        self.code.len() - 1
+    }
+    
+    fn add_constant(&mut self, value: Value) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+    
+    // Points the jump at `at` to the next instruction to be emitted
+    fn patch_jump(&mut self, at: usize) {
+        let target = self.code.len();
+        match &mut self.code[at] {
+            Instruction::Jump(to)
+            | Instruction::JumpIfFalse(to)
+            | Instruction::JumpIfFalseOrPop(to)
+            | Instruction::JumpIfTrueOrPop(to) => *to = target,
+            other => unreachable!("cannot patch {:?}", other),
+        }
+    }
+}
+
+// A compiled 'dala' function, or the top-level script
+#[derive(Debug)]
+pub(crate) struct FunctionProto {
+    pub(crate) name: String,
+    pub(crate) arity: usize,
+    // Parameters plus every local declared in the body
+    pub(crate) local_count: usize,
+    pub(crate) chunk: Chunk,
+}
+
+impl FunctionProto {
+    // Disassembly listing, naming globals and functions from the tables they were compiled with
+    pub(crate) fn listing<'a>(
+        &'a self,
+        globals: &'a Table<Value>,
+        functions: &'a Table<Rc<FunctionProto>>,
+    ) -> Listing<'a> {
+        Listing { function: self, globals, functions }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Listing<'a> {
+    function: &'a FunctionProto,
+    globals: &'a Table<Value>,
+    functions: &'a Table<Rc<FunctionProto>>,
+}
+
+// The function's bytecode, followed by its nested functions
+impl fmt::Display for Listing<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chunk = &self.function.chunk;
+        writeln!(f, "== {} ==", self.function.name)?;
+        for (i, instruction) in chunk.code.iter().enumerate() {
+            let detail = match instruction {
+                Instruction::Constant(index) => format!("{:?}", chunk.constants[*index]),
+                Instruction::DefineGlobal(index) | Instruction::GetGlobal(index) | Instruction::SetGlobal(index) => {
+                    self.globals.name(*index).to_string()
+                }
+                Instruction::Call(index, _) => self.functions.name(*index).to_string(),
+                Instruction::DefineFunction(index, _) => chunk.functions[*index].name.clone(),
+                _ => String::new(),
+            };
+            writeln!(f, "{:04} {:>4}:{:<3} {:?} {}", i, chunk.spans[i].line, chunk.spans[i].column, instruction, detail)?;
+        }
+        for function in &chunk.functions {
+            write!(f, "\n{}", Listing { function, ..*self })?;
+        }
+        Ok(())
+    }
+}
+
+struct LoopContext {
+    start: usize,
+    breaks: Vec<usize>,
+}
+
+// A variable declared in a function or block, living in a stack slot
+struct Local {
+    name: String,
+    depth: usize,
+}
+
+// Compiles the AST into bytecode, resolving locals to stack slots, and globals and
+// functions to their indexes in the interpreter's tables
+pub(crate) struct Compiler<'a> {
+    chunk: Chunk,
+    globals: &'a mut Table<Value>,
+    functions: &'a mut Table<Rc<FunctionProto>>,
+    // Locals currently in scope, indexed by slot; a slot is reused once its block ends
+    locals: Vec<Local>,
+    // Number of blocks around the code being compiled; variables at depth 0 are globals
+    scope_depth: usize,
+    // Most slots in use at once, reserved when the code starts running
+    slot_count: usize,
+    loops: Vec<LoopContext>,
+}
+
+impl<'a> Compiler<'a> {
+    pub(crate) fn compile_script(
+        statements: &[Statement],
+        globals: &'a mut Table<Value>,
+        functions: &'a mut Table<Rc<FunctionProto>>,
+    ) -> Rc<FunctionProto> {
+        let mut compiler = Compiler {
+            chunk: Chunk::default(),
+            globals,
+            functions,
+            locals: Vec::new(),
+            scope_depth: 0,
+            slot_count: 0,
+            loops: Vec::new(),
+        };
+        match statements.split_last() {
+            // The value of a trailing expression statement is the script's result
+            Some((Statement { kind: StatementKind::Expression(expr), span }, rest)) => {
+                compiler.compile_block(rest);
+                compiler.compile_expr(expr);
+                compiler.emit(Instruction::Return, *span);
+            }
+            _ => compiler.compile_block(statements),
+        }
+        compiler.finish("<script>".to_string(), 0)
+    }
+    
+    fn compile_function(&mut self, name: &str, params: &[Param], body: &[Statement]) -> Rc<FunctionProto> {
+        // Parameters are the function body's outermost locals
+        let locals: Vec<Local> = params
+            .iter()
+            .map(|param| Local { name: param.name.clone(), depth: 1 })
+            .collect();
+        let mut compiler = Compiler {
+            chunk: Chunk::default(),
+            globals: self.globals,
+            functions: self.functions,
+            slot_count: locals.len(),
+            locals,
+            scope_depth: 1,
+            loops: Vec::new(),
+        };
+        compiler.compile_block(body);
+        compiler.finish(name.to_string(), params.len())
+    }
+    
+    // Adds the implicit `rajje3 walashi` at the end of the code
+    fn finish(mut self, name: String, arity: usize) -> Rc<FunctionProto> {
+        let span = self.chunk.spans.last().copied().unwrap_or_default();
+        self.chunk.emit(Instruction::Nil, span);
+        self.chunk.emit(Instruction::Return, span);
+        Rc::new(FunctionProto {
+            name,
+            arity,
+            local_count: self.slot_count,
+            chunk: self.chunk,
+        })
+    }
+    
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.locals.iter().rposition(|local| local.name == name)
+    }
+    
+    // Slot for a 'hayde' in the current block; redeclaring in the same block reuses the slot
+    fn declare_local(&mut self, name: &str) -> usize {
+        let existing = self
+            .locals
+            .iter()
+            .rposition(|local| local.depth == self.scope_depth && local.name == name);
+        if let Some(slot) = existing {
+            return slot;
+        }
+        self.locals.push(Local { name: name.to_string(), depth: self.scope_depth });
+        self.slot_count = self.slot_count.max(self.locals.len());
+        self.locals.len() - 1
+    }
+    
+    // Compiles an 'eza' or 'khalas' body in its own scope
+    fn compile_scoped_block(&mut self, statements: &[Statement]) {
+        self.scope_depth += 1;
+        self.compile_block(statements);
+        self.scope_depth -= 1;
+        while self.locals.last().is_some_and(|local| local.depth > self.scope_depth) {
+            self.locals.pop();
+        }
+    }
+    
+    fn emit(&mut self, instruction: Instruction, span: Span) -> usize {
+        self.chunk.emit(instruction, span)
+    }
+    
+    fn compile_block(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            self.compile_statement(stmt);
+        }
+    }
+    
+    fn compile_statement(&mut self, stmt: &Statement) {
+        let span = stmt.span;
+        match &stmt.kind {
+            StatementKind::VarDecl { name, value } => {
+                self.compile_expr(value);
+                if self.scope_depth > 0 {
+                    let slot = self.declare_local(name);
+                    self.emit(Instruction::SetLocal(slot), span);
+                } else {
+                    let index = self.globals.index(name);
+                    self.emit(Instruction::DefineGlobal(index), span);
+                }
+            }
+            StatementKind::Assignment { name, value } => {
+                self.compile_expr(value);
+                match self.resolve_local(name) {
+                    Some(slot) => self.emit(Instruction::SetLocal(slot), span),
+                    None => {
+                        let index = self.globals.index(name);
+                        self.emit(Instruction::SetGlobal(index), span)
+                    }
+                };
+            }
+            StatementKind::IndexAssign { target, index, value } => {
+                self.compile_expr(target);
+                self.compile_expr(index);
+                self.compile_expr(value);
+                self.emit(Instruction::SetIndex, span);
+            }
+            StatementKind::Print(expr) => {
+                self.compile_expr(expr);
+                self.emit(Instruction::Print, span);
+            }
+            StatementKind::Debug(expr) => {
+                self.compile_expr(expr);
+                self.emit(Instruction::Debug, span);
+            }
+            StatementKind::If { condition, then_body, else_body } => {
+                self.compile_expr(condition);
+                let to_else = self.emit(Instruction::JumpIfFalse(0), span);
+                self.compile_scoped_block(then_body);
+                let to_end = self.emit(Instruction::Jump(0), span);
+                self.chunk.patch_jump(to_else);
+                if let Some(else_body) = else_body {
+                    self.compile_scoped_block(else_body);
+                }
+                self.chunk.patch_jump(to_end);
+            }
+            StatementKind::While { condition, body } => {
+                let start = self.chunk.code.len();
+                self.compile_expr(condition);
+                let to_exit = self.emit(Instruction::JumpIfFalse(0), span);
+                self.loops.push(LoopContext { start, breaks: Vec::new() });
+                self.compile_scoped_block(body);
+                self.emit(Instruction::Jump(start), span);
+                self.chunk.patch_jump(to_exit);
+                if let Some(context) = self.loops.pop() {
+                    for at in context.breaks {
+                        self.chunk.patch_jump(at);
+                    }
+                }
+            }
+            StatementKind::Function { name, params, body } => {
+                let function = self.compile_function(name, params, body);
+                self.chunk.functions.push(function);
+                let index = self.chunk.functions.len() - 1;
+                let table_index = self.functions.index(name);
+                self.emit(Instruction::DefineFunction(index, table_index), span);
+            }
+            StatementKind::Return(expr) => {
+                match expr {
+                    Some(expr) => self.compile_expr(expr),
+                    None => {
+                        self.emit(Instruction::Nil, span);
+                    }
+                }
+                self.emit(Instruction::Return, span);
+            }
+            StatementKind::Expression(expr) => {
+                self.compile_expr(expr);
+                self.emit(Instruction::Pop, span);
+            }
+            // The resolver rejects 'waqif' and 'yalla' outside a loop, so there's
+            // always a loop to jump in
+            StatementKind::Break => {
+                let at = self.emit(Instruction::Jump(0), span);
+                if let Some(context) = self.loops.last_mut() {
+                    context.breaks.push(at);
+                }
+            }
+            StatementKind::Continue => {
+                if let Some(context) = self.loops.last() {
+                    let start = context.start;
+                    self.emit(Instruction::Jump(start), span);
+                }
+            }
+        }
+    }
+    
+    fn compile_expr(&mut self, expr: &Expr) {
+        let span = expr.span;
+        match &expr.kind {
+            ExprKind::Int(n) => {
+                let index = self.chunk.add_constant(Value::Int(*n));
+                self.emit(Instruction::Constant(index), span);
+            }
+            ExprKind::BigInt(n) => {
+                let index = self.chunk.add_constant(Value::from(n.clone()));
+                self.emit(Instruction::Constant(index), span);
+            }
+            ExprKind::Float(n) => {
+                let index = self.chunk.add_constant(Value::Float(*n));
+                self.emit(Instruction::Constant(index), span);
+            }
+            ExprKind::String(s) => {
+                let index = self.chunk.add_constant(Value::String(s.clone()));
+                self.emit(Instruction::Constant(index), span);
+            }
+            ExprKind::Bool(true) => {
+                self.emit(Instruction::True, span);
+            }
+            ExprKind::Bool(false) => {
+                self.emit(Instruction::False, span);
+            }
+            ExprKind::Nil => {
+                self.emit(Instruction::Nil, span);
+            }
+            ExprKind::Identifier(name) => {
+                match self.resolve_local(name) {
+                    Some(slot) => self.emit(Instruction::GetLocal(slot), span),
+                    None => {
+                        let index = self.globals.index(name);
+                        self.emit(Instruction::GetGlobal(index), span)
+                    }
+                };
+            }
+            ExprKind::Call { name, args } => {
+                for arg in args {
+                    self.compile_expr(arg);
+                }
+                let index = self.functions.index(name);
+                self.emit(Instruction::Call(index, args.len()), span);
+            }
+            ExprKind::List(items) => {
+                for item in items {
+                    self.compile_expr(item);
+                }
+                self.emit(Instruction::BuildList(items.len()), span);
+            }
+            ExprKind::Interpolation(parts) => {
+                for part in parts {
+                    self.compile_expr(part);
+                }
+                self.emit(Instruction::Concat(parts.len()), span);
+            }
+            ExprKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.compile_expr(key);
+                    self.compile_expr(value);
+                }
+                self.emit(Instruction::BuildMap(entries.len()), span);
+            }
+            ExprKind::Index { target, index } => {
+                self.compile_expr(target);
+                self.compile_expr(index);
+                self.emit(Instruction::Index, span);
+            }
+            ExprKind::Binary { left, op: BinaryOp::And, right } => {
+                self.compile_expr(left);
+                let to_end = self.emit(Instruction::JumpIfFalseOrPop(0), span);
+                self.compile_expr(right);
+                self.chunk.patch_jump(to_end);
+            }
+            ExprKind::Binary { left, op: BinaryOp::Or, right } => {
+                self.compile_expr(left);
+                let to_end = self.emit(Instruction::JumpIfTrueOrPop(0), span);
+                self.compile_expr(right);
+                self.chunk.patch_jump(to_end);
+            }
+            ExprKind::Binary { left, op, right } => {
+                self.compile_expr(left);
+                self.compile_expr(right);
+                self.emit(Instruction::Binary(*op), span);
+            }
+            ExprKind::Unary { op, expr } => {
+                self.compile_expr(expr);
+                let instruction = match op {
+                    UnaryOp::Not => Instruction::Not,
+                    UnaryOp::Minus => Instruction::Negate,
+                };
+                self.emit(instruction, span);
+            }
+        }
+    }
+}
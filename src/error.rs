@@ -0,0 +1,204 @@
+use std::fmt;
+
+use crate::lexer::{LexError, Span};
+use crate::parser::ParseError;
+use crate::resolver::ResolveError;
+
+/// What went wrong while running a program
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    UndefinedVariable(String),
+    // Assignment to a name no 'hayde' has declared
+    UndeclaredAssignment(String),
+    UndefinedFunction(String),
+    // A binary operator was applied to values it doesn't support
+    InvalidOperands {
+        op: &'static str,
+        left: &'static str,
+        right: &'static str,
+    },
+    // A unary operator was applied to a value it doesn't support
+    InvalidOperand {
+        op: &'static str,
+        operand: &'static str,
+    },
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    ArgumentType {
+        function: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    ArityMismatch {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    DivisionByZero,
+    InvalidIndex(String),
+    IndexOutOfRange {
+        // What was indexed: "list" or "string"
+        target: &'static str,
+        index: usize,
+        length: usize,
+    },
+    KeyNotFound(String),
+    StackOverflow(usize),
+    Custom(String),
+    // exit(code) was called. Not a failure: it unwinds the program so the host can stop.
+    Exit(i32),
+}
+
+impl fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeErrorKind::UndefinedVariable(name) => write!(f, "Undefined variable '{}'", name),
+            RuntimeErrorKind::UndeclaredAssignment(name) => write!(
+                f,
+                "Cannot assign to undeclared variable '{}'; declare it with 'hayde' first",
+                name
+            ),
+            RuntimeErrorKind::UndefinedFunction(name) => write!(f, "Undefined function '{}'", name),
+            RuntimeErrorKind::InvalidOperands { op, left, right } => {
+                write!(f, "Cannot apply '{}' to {} and {}", op, left, right)
+            }
+            RuntimeErrorKind::InvalidOperand { op, operand } => {
+                write!(f, "Cannot apply '{}' to {}", op, operand)
+            }
+            RuntimeErrorKind::TypeMismatch { expected, found } => {
+                write!(f, "Expected {}, found {}", expected, found)
+            }
+            RuntimeErrorKind::ArgumentType { function, expected, found } => {
+                write!(f, "{}() expected {}, found {}", function, expected, found)
+            }
+            RuntimeErrorKind::ArityMismatch { function, expected, found } => write!(
+                f,
+                "{}() takes {} argument{}, got {}",
+                function,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            RuntimeErrorKind::DivisionByZero => write!(f, "Division by zero"),
+            RuntimeErrorKind::InvalidIndex(index) => {
+                write!(f, "Index must be a non-negative integer, got {}", index)
+            }
+            RuntimeErrorKind::IndexOutOfRange { target, index, length } => write!(
+                f,
+                "Index {} out of range for {} of length {}",
+                index, target, length
+            ),
+            RuntimeErrorKind::KeyNotFound(key) => write!(f, "Key not found: {:?}", key),
+            RuntimeErrorKind::StackOverflow(depth) => {
+                write!(f, "Stack overflow: more than {} nested calls", depth)
+            }
+            RuntimeErrorKind::Custom(message) => write!(f, "{}", message),
+            RuntimeErrorKind::Exit(code) => write!(f, "Exited with code {}", code),
+        }
+    }
+}
+
+/// A function call that was active when an error happened
+#[derive(Debug, Clone)]
+pub struct StackFrame {
+    pub function: String,
+    pub call_site: Span,
+}
+
+/// Errors that stop a running program
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    pub span: Option<Span>,
+    // Innermost call first
+    pub stack: Vec<StackFrame>,
+}
+
+impl RuntimeError {
+    pub fn new(kind: RuntimeErrorKind) -> Self {
+        RuntimeError { kind, span: None, stack: Vec::new() }
+    }
+    
+    // Records where the error happened, keeping the innermost location
+    pub(crate) fn at(mut self, span: Span) -> Self {
+        self.span.get_or_insert(span);
+        self
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "Runtime error at {}: {}", span, self.kind)?,
+            None => write!(f, "Runtime error: {}", self.kind)?,
+        }
+        // Runs of the same frame (deep recursion) are collapsed into one line
+        let mut i = 0;
+        while i < self.stack.len() {
+            let frame = &self.stack[i];
+            let repeats = self.stack[i..]
+                .iter()
+                .take_while(|other| other.function == frame.function && other.call_site == frame.call_site)
+                .count();
+            write!(f, "\n    in {}(), called from {}", frame.function, frame.call_site)?;
+            if repeats > 1 {
+                write!(f, "\n    ... repeated {} more times", repeats - 1)?;
+            }
+            i += repeats;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+/// Anything that can stop a piece of source code from running to completion
+#[derive(Debug, Clone)]
+pub enum Error {
+    Lex(LexError),
+    Parse(ParseError),
+    // Every problem the resolver found, in source order
+    Resolve(Vec<ResolveError>),
+    Runtime(RuntimeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Lex(err) => write!(f, "{}", err),
+            Error::Parse(err) => write!(f, "{}", err),
+            Error::Resolve(errors) => {
+                for (i, err) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", err)?;
+                }
+                Ok(())
+            }
+            Error::Runtime(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<LexError> for Error {
+    fn from(err: LexError) -> Self {
+        Error::Lex(err)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(err: RuntimeError) -> Self {
+        Error::Runtime(err)
+    }
+}
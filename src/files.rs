@@ -0,0 +1,149 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::RuntimeError;
+use crate::native::NativeArgs;
+use crate::value::Value;
+
+// A file built-in; may only touch files the sandbox allows
+pub(crate) type FileFunction = fn(&Sandbox, &NativeArgs) -> Result<Value, RuntimeError>;
+
+// Directories scripts may use files in. Empty by default, which disables file access.
+#[derive(Debug, Default)]
+pub(crate) struct Sandbox {
+    // Canonical paths, so symlinks and '..' can't lead outside them
+    roots: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    pub(crate) fn allow(&mut self, dir: &Path) -> io::Result<()> {
+        let root = dir.canonicalize()?;
+        if !root.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::NotADirectory, "not a directory"));
+        }
+        self.roots.push(root);
+        Ok(())
+    }
+    
+    // Resolves the path a script gave, failing unless it's inside an allowed directory.
+    // The path doesn't have to exist: the part that does is resolved, and the missing
+    // part after it is kept as written.
+    fn resolve(&self, args: &NativeArgs, path: &str) -> Result<PathBuf, RuntimeError> {
+        if self.roots.is_empty() {
+            return Err(args.error("file access is disabled; allow it with --allow-fs=<dir>"));
+        }
+        let mut existing = PathBuf::from(path);
+        let mut missing = Vec::new();
+        let base = loop {
+            if existing.as_os_str().is_empty() {
+                existing.push(".");
+            }
+            let err = match existing.canonicalize() {
+                Ok(base) => break base,
+                Err(err) => err,
+            };
+            // A broken symlink could point anywhere once written through
+            if existing.symlink_metadata().is_ok_and(|meta| meta.is_symlink()) {
+                return Err(args.error(format!("can't access '{}': broken symlink", path)));
+            }
+            match existing.components().next_back() {
+                Some(part @ (Component::Normal(_) | Component::ParentDir)) => {
+                    missing.push(part.as_os_str().to_owned());
+                }
+                _ => return Err(io_error(args, "access", path, err)),
+            }
+            existing.pop();
+        };
+        
+        // Nothing exists below a missing directory, so '..' after one can only be
+        // followed on paper to see where the path points
+        let mut resolved = base.clone();
+        let mut lexical = base;
+        for part in missing.into_iter().rev() {
+            if part == ".." {
+                lexical.pop();
+            } else {
+                lexical.push(&part);
+            }
+            resolved.push(part);
+        }
+        if !self.roots.iter().any(|root| lexical.starts_with(root)) {
+            let message = format!("'{}' is outside the directories file access is allowed in", path);
+            return Err(args.error(message));
+        }
+        Ok(resolved)
+    }
+}
+
+pub(crate) fn lookup(name: &str) -> Option<FileFunction> {
+    let function: FileFunction = match name {
+        "read_file" => read_file,
+        "read_lines" => read_lines,
+        "write_file" => |sandbox, args| write(sandbox, args, false),
+        "append_file" => |sandbox, args| write(sandbox, args, true),
+        "file_exists" => file_exists,
+        "list_dir" => list_dir,
+        _ => return None,
+    };
+    Some(function)
+}
+
+// read_file(path): the whole file as a string
+fn read_file(sandbox: &Sandbox, args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    let path = args.string(0)?;
+    let text = fs::read_to_string(sandbox.resolve(args, path)?)
+        .map_err(|err| io_error(args, "read", path, err))?;
+    Ok(Value::String(text))
+}
+
+// read_lines(path): list of the file's lines, without their line endings
+fn read_lines(sandbox: &Sandbox, args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    let path = args.string(0)?;
+    let text = fs::read_to_string(sandbox.resolve(args, path)?)
+        .map_err(|err| io_error(args, "read", path, err))?;
+    Ok(Value::list(text.lines().map(Value::from).collect()))
+}
+
+// write_file(path, value) / append_file(path, value): writes the value the way '3mol'
+// prints it, replacing the file or adding to its end; the file is created if needed
+fn write(sandbox: &Sandbox, args: &NativeArgs, append: bool) -> Result<Value, RuntimeError> {
+    args.expect_arity(2)?;
+    let path = args.string(0)?;
+    let resolved = sandbox.resolve(args, path)?;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(resolved)
+        .map_err(|err| io_error(args, "write", path, err))?;
+    write!(file, "{}", args.get(1)?).map_err(|err| io_error(args, "write", path, err))?;
+    Ok(Value::Nil)
+}
+
+fn file_exists(sandbox: &Sandbox, args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    Ok(Value::Bool(sandbox.resolve(args, args.string(0)?)?.exists()))
+}
+
+// list_dir(path): sorted names of the entries in a directory
+fn list_dir(sandbox: &Sandbox, args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    let path = args.string(0)?;
+    let entries = fs::read_dir(sandbox.resolve(args, path)?)
+        .map_err(|err| io_error(args, "list", path, err))?;
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|err| io_error(args, "list", path, err))?;
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names.sort();
+    Ok(Value::list(names.into_iter().map(Value::String).collect()))
+}
+
+fn io_error(args: &NativeArgs, action: &str, path: &str, err: io::Error) -> RuntimeError {
+    args.error(format!("can't {} '{}': {}", action, path, err))
+}
@@ -0,0 +1,910 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::ast::{BinaryOp, Expr, ExprKind, Param, Statement, StatementKind, UnaryOp};
+use crate::bigint::BigInt;
+use crate::compiler::{Compiler, FunctionProto};
+use crate::error::{Error, RuntimeError, RuntimeErrorKind, StackFrame};
+use crate::files::{self, Sandbox};
+use crate::lexer::Span;
+use crate::math;
+use crate::native::{NativeArgs, NativeFunction};
+use crate::random::{self, Rng};
+use crate::resolver::{Resolver, Warning};
+use crate::stack;
+use crate::strings;
+use crate::system;
+use crate::table::Table;
+use crate::value::Value;
+use crate::vm::Vm;
+
+/// Which engine runs programs; both give the same results, the VM is faster.
+///
+/// Both allow scripts 1000 nested function calls. The VM keeps its call stack on the
+/// heap, but the tree walker nests Rust calls on the thread's stack for every script
+/// call, so on a thread with a small stack it stops deep recursion with an error before
+/// that; see `Interpreter::set_stack_limit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    TreeWalker,
+    #[default]
+    Vm,
+}
+
+// Deepest chain of nested function calls before giving up
+pub(crate) const MAX_CALL_DEPTH: usize = 1000;
+
+// Stack the tree walker leaves unused for the work between two calls' checks, like
+// nested blocks and expressions, built-ins and the host's error handling
+const STACK_RESERVE: usize = 256 * 1024;
+
+// Stack the tree walker may use where the platform doesn't tell how big the thread's is;
+// leaves room to spare on any thread Rust starts (2 MiB unless asked for more)
+const FALLBACK_STACK_LIMIT: usize = 1024 * 1024;
+
+// Control flow signals
+#[derive(Debug)]
+enum FlowControl {
+    None,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+// A user-defined function registered by a 'dala' declaration
+#[derive(Debug)]
+struct Function {
+    params: Vec<String>,
+    body: Rc<[Statement]>,
+}
+
+/// Runs Faysal Lang code; globals and functions persist between runs
+pub struct Interpreter {
+    backend: Backend,
+    pub(crate) globals: Table<Value>,
+    functions: HashMap<String, Rc<Function>>,
+    // Functions declared while running on the VM
+    pub(crate) compiled_functions: Table<Rc<FunctionProto>>,
+    // Functions provided by the host program
+    natives: HashMap<String, NativeFunction>,
+    // Where '3mol' and 'lowkey' write to
+    output: Box<dyn Write>,
+    debug_output: Box<dyn Write>,
+    // Where input() reads from; stdin when None
+    input: Option<Box<dyn BufRead>>,
+    // Source of random() and friends
+    rng: Rng,
+    // Where the file built-ins may read and write
+    sandbox: Sandbox,
+    // Block scopes of the running code, innermost last; globals live in `globals`.
+    // A function call swaps in a fresh chain holding its parameters.
+    scopes: Vec<HashMap<String, Value>>,
+    call_depth: usize,
+    // Bytes of stack the tree walker may use, counted from where the running program
+    // started, before stopping with a stack overflow error instead of crashing the host;
+    // all the thread has left but the reserve unless the host sets a limit
+    stack_limit: Option<usize>,
+    // The lowest stack address calls of the running program may start at
+    stack_floor: usize,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    /// Creates an interpreter using the default backend, the VM
+    pub fn new() -> Self {
+        Interpreter::with_backend(Backend::default())
+    }
+    
+    /// Creates an interpreter using the given backend
+    pub fn with_backend(backend: Backend) -> Self {
+        let mut globals = Table::default();
+        for (name, value) in math::CONSTANTS {
+            globals.insert(name, Value::Float(value));
+        }
+        globals.insert(system::ARGS, Value::list(Vec::new()));
+        Interpreter {
+            backend,
+            globals,
+            functions: HashMap::new(),
+            compiled_functions: Table::default(),
+            natives: HashMap::new(),
+            output: Box::new(io::stdout()),
+            debug_output: Box::new(io::stderr()),
+            input: None,
+            rng: Rng::from_entropy(),
+            sandbox: Sandbox::default(),
+            scopes: Vec::new(),
+            call_depth: 0,
+            stack_limit: None,
+            stack_floor: 0,
+        }
+    }
+    
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+    
+    /// Parses and runs `source`, returning its result: the value given to a
+    /// top-level 'rajje3', or of a trailing expression statement, or walashi
+    pub fn eval(&mut self, source: &str) -> Result<Value, Error> {
+        let program = crate::parse(source)?;
+        self.run(program)
+    }
+    
+    /// Runs an already parsed program; see `eval`
+    pub fn run(&mut self, program: Vec<Statement>) -> Result<Value, Error> {
+        self.check(&program)?;
+        let result = match self.backend {
+            Backend::TreeWalker => self.execute(&program).map_err(|err| *err),
+            Backend::Vm => {
+                let script = Compiler::compile_script(&program, &mut self.globals, &mut self.compiled_functions);
+                Vm::new().run(self, script)
+            }
+        };
+        Ok(result?)
+    }
+    
+    /// Resolves a program's variables without running it, as `run` does first.
+    /// Returns the warnings (like unused variables) if no errors were found.
+    pub fn check(&self, program: &[Statement]) -> Result<Vec<Warning>, Error> {
+        Resolver::resolve(program, self.globals.names()).map_err(Error::Resolve)
+    }
+    
+    /// Value of a global variable, if it's defined
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.globals.get(name).cloned()
+    }
+    
+    /// Defines or overwrites a global variable
+    pub fn set_global(&mut self, name: &str, value: impl Into<Value>) {
+        self.globals.insert(name, value.into());
+    }
+    
+    /// Makes a Rust closure callable from scripts as `name(...)`. A 'dala' function with
+    /// the same name takes precedence; the native one shadows any built-in.
+    pub fn register_native<F>(&mut self, name: &str, function: F)
+    where
+        F: Fn(&NativeArgs) -> Result<Value, RuntimeError> + 'static,
+    {
+        self.natives.insert(name.to_string(), Rc::new(function));
+    }
+    
+    /// Sends '3mol' output to `writer` instead of stdout
+    pub fn set_output(&mut self, writer: impl Write + 'static) {
+        self.output = Box::new(writer);
+    }
+    
+    /// Sends 'lowkey' output to `writer` instead of stderr
+    pub fn set_debug_output(&mut self, writer: impl Write + 'static) {
+        self.debug_output = Box::new(writer);
+    }
+    
+    /// Makes input() read lines from `reader` instead of stdin
+    pub fn set_input(&mut self, reader: impl BufRead + 'static) {
+        self.input = Some(Box::new(reader));
+    }
+    
+    /// Seeds random(), random_int() and choice(), so they give the same numbers on every run
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+    
+    /// Sets the list scripts see as ARGS, which is empty by default
+    pub fn set_args(&mut self, args: impl IntoIterator<Item = impl Into<String>>) {
+        let args = args.into_iter().map(|arg| Value::String(arg.into())).collect();
+        self.set_global(system::ARGS, Value::list(args));
+    }
+    
+    /// Lets the tree walker use up to `bytes` of stack for nested function calls; past
+    /// that, scripts stop with a stack overflow error (the VM isn't limited by the stack).
+    ///
+    /// By default it may use all of the stack the thread has left but 256 KiB, as the
+    /// operating system reports it, or 1 MiB on platforms where it doesn't. 1000 nested
+    /// calls take about 1.5 MiB in release builds and 4 MiB in debug builds, more when
+    /// each call sits inside 'eza' or 'khalas' blocks.
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.stack_limit = Some(bytes);
+    }
+    
+    /// Lets the file built-ins (read_file, write_file, ...) use files inside `dir` and
+    /// its subdirectories. File access is disabled until this is called; fails if `dir`
+    /// isn't an existing directory.
+    pub fn allow_fs(&mut self, dir: impl AsRef<Path>) -> io::Result<()> {
+        self.sandbox.allow(dir.as_ref())
+    }
+    
+    // Output of a '3mol' statement
+    pub(crate) fn print(&mut self, val: &Value) -> Result<(), RuntimeError> {
+        writeln!(self.output, "{}", val).map_err(output_error)
+    }
+    
+    // Output of a 'lowkey' statement
+    pub(crate) fn debug(&mut self, val: &Value) -> Result<(), RuntimeError> {
+        writeln!(self.debug_output, "[DEBUG] {}", val).map_err(output_error)
+    }
+    
+    // input(prompt): shows the optional prompt, then reads a line without its line
+    // ending; walashi once the input has run out
+    fn read_input(&mut self, args: &NativeArgs) -> Result<Value, RuntimeError> {
+        if args.len() > 1 {
+            args.expect_arity(1)?;
+        }
+        if !args.is_empty() {
+            write!(self.output, "{}", args.get(0)?).map_err(output_error)?;
+        }
+        self.output.flush().map_err(output_error)?;
+        
+        let mut line = String::new();
+        let read = match &mut self.input {
+            Some(reader) => reader.read_line(&mut line),
+            None => io::stdin().read_line(&mut line),
+        };
+        match read {
+            Ok(0) => Ok(Value::Nil),
+            Ok(_) => {
+                let line = line.strip_suffix('\n').unwrap_or(&line);
+                Ok(Value::from(line.strip_suffix('\r').unwrap_or(line)))
+            }
+            Err(err) => Err(args.error(format!("failed to read input: {}", err))),
+        }
+    }
+    
+    // The tree walker boxes its errors, which keeps the results passed back through every
+    // level of nesting small
+    fn execute(&mut self, statements: &[Statement]) -> Result<Value, Box<RuntimeError>> {
+        let base = stack::address();
+        self.stack_floor = match (self.stack_limit, stack::end()) {
+            (Some(limit), _) => base.saturating_sub(limit),
+            (None, Some(end)) => end.saturating_add(STACK_RESERVE),
+            (None, None) => base.saturating_sub(FALLBACK_STACK_LIMIT),
+        };
+        // The value of a trailing expression statement is the program's result
+        let (statements, result_expr) = match statements.split_last() {
+            Some((Statement { kind: StatementKind::Expression(expr), span }, rest)) => {
+                (rest, Some((expr, *span)))
+            }
+            _ => (statements, None),
+        };
+        for stmt in statements {
+            match self.execute_statement(stmt)? {
+                FlowControl::Return(val) => return Ok(val),
+                FlowControl::Break => return Ok(Value::Nil),
+                FlowControl::Continue | FlowControl::None => {}
+            }
+        }
+        match result_expr {
+            Some((expr, span)) => self.eval_expr(expr).map_err(|mut err| {
+                err.span.get_or_insert(span);
+                err
+            }),
+            None => Ok(Value::Nil),
+        }
+    }
+    
+    fn lookup_variable(&self, name: &str) -> Option<&Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.globals.get(name))
+    }
+    
+    // 'hayde' always creates the variable in the innermost scope, shadowing outer ones
+    fn declare_variable(&mut self, name: String, val: Value) {
+        match self.scopes.last_mut() {
+            Some(scope) => {
+                scope.insert(name, val);
+            }
+            None => self.globals.insert(&name, val),
+        }
+    }
+    
+    // Assignment updates the nearest existing variable and never creates one
+    fn assign_variable(&mut self, name: &str, val: Value) -> Result<(), RuntimeError> {
+        let variable = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .or_else(|| self.globals.get_mut(name));
+        match variable {
+            Some(variable) => {
+                *variable = val;
+                Ok(())
+            }
+            None => Err(RuntimeError::new(RuntimeErrorKind::UndeclaredAssignment(name.to_string()))),
+        }
+    }
+    
+    fn call_function(&mut self, name: &str, args: Vec<Value>, call_site: Span) -> Result<Value, Box<RuntimeError>> {
+        let function = match self.functions.get(name) {
+            Some(function) => Rc::clone(function),
+            None => return self.call_builtin(name, args).map_err(|err| Box::new(err.at(call_site))),
+        };
+        
+        let caller_scopes = self.enter_function(name, &function.params, args, call_site)?;
+        let result = self.run_function_body(&function.body);
+        self.call_depth -= 1;
+        self.scopes = caller_scopes;
+        
+        result.map_err(|err| called_from(err, name, call_site))
+    }
+    
+    // Checks that a 'dala' function can be called and gives it a fresh scope chain holding
+    // its parameters, returning the caller's. Kept out of call_function, which stays on
+    // the stack while the function runs.
+    #[inline(never)]
+    fn enter_function(
+        &mut self,
+        name: &str,
+        params: &[String],
+        args: Vec<Value>,
+        call_site: Span,
+    ) -> Result<Vec<HashMap<String, Value>>, Box<RuntimeError>> {
+        if args.len() != params.len() {
+            let kind = RuntimeErrorKind::ArityMismatch {
+                function: name.to_string(),
+                expected: params.len(),
+                found: args.len(),
+            };
+            return Err(Box::new(RuntimeError::new(kind).at(call_site)));
+        }
+        if self.call_depth >= MAX_CALL_DEPTH || stack::address() < self.stack_floor {
+            let kind = RuntimeErrorKind::StackOverflow(self.call_depth);
+            return Err(Box::new(RuntimeError::new(kind).at(call_site)));
+        }
+        
+        self.call_depth += 1;
+        let frame = params.iter().cloned().zip(args).collect();
+        Ok(std::mem::replace(&mut self.scopes, vec![frame]))
+    }
+    
+    fn run_function_body(&mut self, body: &[Statement]) -> Result<Value, Box<RuntimeError>> {
+        for stmt in body {
+            match self.execute_statement(stmt)? {
+                FlowControl::Return(val) => return Ok(val),
+                FlowControl::Break | FlowControl::Continue => break,
+                FlowControl::None => {}
+            }
+        }
+        Ok(Value::Nil)
+    }
+    
+    // Functions that are always available without a 'dala' declaration
+    pub(crate) fn call_builtin(&mut self, name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        if let Some(native) = self.natives.get(name) {
+            return native(&NativeArgs::new(name, &args));
+        }
+        let library = strings::lookup(name)
+            .or_else(|| math::lookup(name))
+            .or_else(|| system::lookup(name));
+        if let Some(function) = library {
+            return function(&NativeArgs::new(name, &args));
+        }
+        if let Some(function) = random::lookup(name) {
+            return function(&mut self.rng, &NativeArgs::new(name, &args));
+        }
+        if let Some(function) = files::lookup(name) {
+            return function(&self.sandbox, &NativeArgs::new(name, &args));
+        }
+        if name == "input" {
+            return self.read_input(&NativeArgs::new(name, &args));
+        }
+        
+        let arity = match name {
+            "len" | "pop" | "keys" | "values" => 1,
+            "push" | "has" | "remove" => 2,
+            _ => {
+                return Err(RuntimeError::new(RuntimeErrorKind::UndefinedFunction(
+                    name.to_string(),
+                )));
+            }
+        };
+        if args.len() != arity {
+            return Err(RuntimeError::new(RuntimeErrorKind::ArityMismatch {
+                function: name.to_string(),
+                expected: arity,
+                found: args.len(),
+            }));
+        }
+        
+        let argument_type = |expected, found: &Value| {
+            RuntimeError::new(RuntimeErrorKind::ArgumentType {
+                function: name.to_string(),
+                expected,
+                found: found.type_name(),
+            })
+        };
+        
+        match (name, args.as_slice()) {
+            ("len", [Value::List(items)]) => Ok(Value::Int(items.borrow().len() as i64)),
+            ("len", [Value::String(s)]) => Ok(Value::Int(s.chars().count() as i64)),
+            ("len", [Value::Map(entries)]) => Ok(Value::Int(entries.borrow().len() as i64)),
+            ("len", [other]) => Err(argument_type("a list, string or map", other)),
+            ("push", [Value::List(items), val]) => {
+                items.borrow_mut().push(val.clone());
+                Ok(Value::Nil)
+            }
+            ("pop", [Value::List(items)]) => items.borrow_mut().pop().ok_or_else(|| {
+                RuntimeError::new(RuntimeErrorKind::Custom("pop() from an empty list".to_string()))
+            }),
+            ("push" | "pop", [other, ..]) => Err(argument_type("a list", other)),
+            ("keys", [Value::Map(entries)]) => Ok(Value::list(
+                entries.borrow().keys().cloned().map(Value::String).collect(),
+            )),
+            ("values", [Value::Map(entries)]) => {
+                Ok(Value::list(entries.borrow().values().cloned().collect()))
+            }
+            ("has", [Value::Map(entries), Value::String(key)]) => {
+                Ok(Value::Bool(entries.borrow().contains_key(key)))
+            }
+            ("remove", [Value::Map(entries), Value::String(key)]) => {
+                Ok(entries.borrow_mut().remove(key).unwrap_or(Value::Nil))
+            }
+            ("has" | "remove", [Value::Map(_), other]) => Err(argument_type("a string key", other)),
+            (_, [other, ..]) => Err(argument_type("a map", other)),
+            _ => unreachable!("arity was checked above"),
+        }
+    }
+    
+    pub(crate) fn map_key(&self, key: Value) -> Result<String, RuntimeError> {
+        match key {
+            Value::String(s) => Ok(s),
+            other => Err(RuntimeError::new(RuntimeErrorKind::TypeMismatch {
+                expected: "a string map key",
+                found: other.type_name(),
+            })),
+        }
+    }
+    
+    pub(crate) fn index_value(&self, target: Value, index: Value) -> Result<Value, RuntimeError> {
+        match target {
+            Value::List(items) => {
+                let items = items.borrow();
+                let i = checked_index(&index, items.len(), "list")?;
+                Ok(items[i].clone())
+            }
+            // Strings are indexed by character, giving a one-character string
+            Value::String(s) => {
+                let chars: Vec<char> = s.chars().collect();
+                let i = checked_index(&index, chars.len(), "string")?;
+                Ok(Value::String(chars[i].to_string()))
+            }
+            Value::Map(entries) => {
+                let key = self.map_key(index)?;
+                entries
+                    .borrow()
+                    .get(&key)
+                    .cloned()
+                    .ok_or(RuntimeError::new(RuntimeErrorKind::KeyNotFound(key)))
+            }
+            other => Err(RuntimeError::new(RuntimeErrorKind::TypeMismatch {
+                expected: "a list, string or map",
+                found: other.type_name(),
+            })),
+        }
+    }
+    
+    pub(crate) fn set_index(&self, target: Value, index: Value, val: Value) -> Result<(), RuntimeError> {
+        match target {
+            Value::List(items) => {
+                let i = checked_index(&index, items.borrow().len(), "list")?;
+                items.borrow_mut()[i] = val;
+            }
+            Value::Map(entries) => {
+                let key = self.map_key(index)?;
+                entries.borrow_mut().insert(key, val);
+            }
+            other => {
+                return Err(RuntimeError::new(RuntimeErrorKind::TypeMismatch {
+                    expected: "a list or map",
+                    found: other.type_name(),
+                }));
+            }
+        }
+        Ok(())
+    }
+    
+    // Runs an 'eza' or 'khalas' body in its own scope
+    fn run_block(&mut self, statements: &[Statement]) -> Result<FlowControl, Box<RuntimeError>> {
+        self.scopes.push(HashMap::new());
+        let mut result = Ok(FlowControl::None);
+        for stmt in statements {
+            match self.execute_statement(stmt) {
+                Ok(FlowControl::None) => {}
+                flow => {
+                    result = flow;
+                    break;
+                }
+            }
+        }
+        self.scopes.pop();
+        result
+    }
+    
+    // Each kind of statement runs in a function of its own, most kept out of line, so the
+    // stack frame of this dispatch, which every nested call adds another of, stays small
+    fn execute_statement(&mut self, stmt: &Statement) -> Result<FlowControl, Box<RuntimeError>> {
+        let span = stmt.span;
+        match &stmt.kind {
+            StatementKind::VarDecl { name, value } => self.execute_var_decl(name, value),
+            StatementKind::Assignment { name, value } => self.execute_assignment(name, value, span),
+            StatementKind::IndexAssign { target, index, value } => {
+                self.execute_index_assign(target, index, value, span)
+            }
+            StatementKind::Print(expr) => self.execute_print(expr, span),
+            StatementKind::Debug(expr) => self.execute_debug(expr, span),
+            StatementKind::If { condition, then_body, else_body } => {
+                self.execute_if(condition, then_body, else_body.as_deref())
+            }
+            StatementKind::While { condition, body } => self.execute_while(condition, body),
+            StatementKind::Function { name, params, body } => self.execute_function(name, params, body),
+            StatementKind::Return(expr) => self.execute_return(expr.as_ref()),
+            StatementKind::Expression(expr) => self.execute_expression(expr),
+            StatementKind::Break => Ok(FlowControl::Break),
+            StatementKind::Continue => Ok(FlowControl::Continue),
+        }
+    }
+    
+    #[inline(never)]
+    fn execute_var_decl(&mut self, name: &str, value: &Expr) -> Result<FlowControl, Box<RuntimeError>> {
+        let val = self.eval_expr(value)?;
+        self.declare_variable(name.to_string(), val);
+        Ok(FlowControl::None)
+    }
+    
+    #[inline(never)]
+    fn execute_assignment(&mut self, name: &str, value: &Expr, span: Span) -> Result<FlowControl, Box<RuntimeError>> {
+        let val = self.eval_expr(value)?;
+        self.assign_variable(name, val).map_err(|err| err.at(span))?;
+        Ok(FlowControl::None)
+    }
+    
+    #[inline(never)]
+    fn execute_index_assign(
+        &mut self,
+        target: &Expr,
+        index: &Expr,
+        value: &Expr,
+        span: Span,
+    ) -> Result<FlowControl, Box<RuntimeError>> {
+        let target_val = self.eval_expr(target)?;
+        let index_val = self.eval_expr(index)?;
+        let val = self.eval_expr(value)?;
+        self.set_index(target_val, index_val, val).map_err(|err| err.at(span))?;
+        Ok(FlowControl::None)
+    }
+    
+    #[inline(never)]
+    fn execute_print(&mut self, expr: &Expr, span: Span) -> Result<FlowControl, Box<RuntimeError>> {
+        let val = self.eval_expr(expr)?;
+        self.print(&val).map_err(|err| err.at(span))?;
+        Ok(FlowControl::None)
+    }
+    
+    #[inline(never)]
+    fn execute_debug(&mut self, expr: &Expr, span: Span) -> Result<FlowControl, Box<RuntimeError>> {
+        let val = self.eval_expr(expr)?;
+        self.debug(&val).map_err(|err| err.at(span))?;
+        Ok(FlowControl::None)
+    }
+    
+    #[inline(never)]
+    fn execute_if(
+        &mut self,
+        condition: &Expr,
+        then_body: &[Statement],
+        else_body: Option<&[Statement]>,
+    ) -> Result<FlowControl, Box<RuntimeError>> {
+        if self.eval_expr(condition)?.is_truthy() {
+            self.run_block(then_body)
+        } else if let Some(else_body) = else_body {
+            self.run_block(else_body)
+        } else {
+            Ok(FlowControl::None)
+        }
+    }
+    
+    #[inline(never)]
+    fn execute_while(&mut self, condition: &Expr, body: &[Statement]) -> Result<FlowControl, Box<RuntimeError>> {
+        while self.eval_expr(condition)?.is_truthy() {
+            match self.run_block(body)? {
+                FlowControl::Break => break,
+                FlowControl::Return(val) => return Ok(FlowControl::Return(val)),
+                FlowControl::Continue | FlowControl::None => {}
+            }
+        }
+        Ok(FlowControl::None)
+    }
+    
+    #[inline(never)]
+    fn execute_function(
+        &mut self,
+        name: &str,
+        params: &[Param],
+        body: &Rc<[Statement]>,
+    ) -> Result<FlowControl, Box<RuntimeError>> {
+        let params = params.iter().map(|param| param.name.clone()).collect();
+        let function = Function { params, body: Rc::clone(body) };
+        self.functions.insert(name.to_string(), Rc::new(function));
+        Ok(FlowControl::None)
+    }
+    
+    fn execute_return(&mut self, expr: Option<&Expr>) -> Result<FlowControl, Box<RuntimeError>> {
+        let val = match expr {
+            Some(expr) => self.eval_expr(expr)?,
+            None => Value::Nil,
+        };
+        Ok(FlowControl::Return(val))
+    }
+    
+    fn execute_expression(&mut self, expr: &Expr) -> Result<FlowControl, Box<RuntimeError>> {
+        self.eval_expr(expr)?;
+        Ok(FlowControl::None)
+    }
+    
+    // Like statements, each kind of expression is evaluated in a function of its own; each
+    // records its span in the errors it causes
+    fn eval_expr(&mut self, expr: &Expr) -> Result<Value, Box<RuntimeError>> {
+        let span = expr.span;
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.eval_identifier(name, span),
+            ExprKind::Call { name, args } => self.eval_call(name, args, span),
+            ExprKind::Interpolation(parts) => self.eval_interpolation(parts),
+            ExprKind::List(items) => self.eval_list(items),
+            ExprKind::Map(entries) => self.eval_map(entries, span),
+            ExprKind::Index { target, index } => self.eval_index(target, index, span),
+            ExprKind::Binary { left, op, right } => self.eval_binary(left, *op, right, span),
+            ExprKind::Unary { op, expr } => self.eval_unary(*op, expr, span),
+            literal => Ok(eval_literal(literal)),
+        }
+    }
+    
+    #[inline(never)]
+    fn eval_identifier(&self, name: &str, span: Span) -> Result<Value, Box<RuntimeError>> {
+        match self.lookup_variable(name) {
+            Some(val) => Ok(val.clone()),
+            None => {
+                let kind = RuntimeErrorKind::UndefinedVariable(name.to_string());
+                Err(Box::new(RuntimeError::new(kind).at(span)))
+            }
+        }
+    }
+    
+    // The call's span is recorded as the call site in stack traces
+    fn eval_call(&mut self, name: &str, args: &[Expr], span: Span) -> Result<Value, Box<RuntimeError>> {
+        let mut arg_vals = Vec::with_capacity(args.len());
+        for arg in args {
+            arg_vals.push(self.eval_expr(arg)?);
+        }
+        self.call_function(name, arg_vals, span)
+    }
+    
+    #[inline(never)]
+    fn eval_interpolation(&mut self, parts: &[Expr]) -> Result<Value, Box<RuntimeError>> {
+        let mut result = String::new();
+        for part in parts {
+            result.push_str(&self.eval_expr(part)?.to_string());
+        }
+        Ok(Value::String(result))
+    }
+    
+    #[inline(never)]
+    fn eval_list(&mut self, items: &[Expr]) -> Result<Value, Box<RuntimeError>> {
+        let mut vals = Vec::with_capacity(items.len());
+        for item in items {
+            vals.push(self.eval_expr(item)?);
+        }
+        Ok(Value::list(vals))
+    }
+    
+    #[inline(never)]
+    fn eval_map(&mut self, entries: &[(Expr, Expr)], span: Span) -> Result<Value, Box<RuntimeError>> {
+        let mut map = BTreeMap::new();
+        for (key, value) in entries {
+            let key = self.eval_expr(key)?;
+            let key = self.map_key(key).map_err(|err| err.at(span))?;
+            let val = self.eval_expr(value)?;
+            map.insert(key, val);
+        }
+        Ok(Value::map(map))
+    }
+    
+    fn eval_index(&mut self, target: &Expr, index: &Expr, span: Span) -> Result<Value, Box<RuntimeError>> {
+        let target_val = self.eval_expr(target)?;
+        let index_val = self.eval_expr(index)?;
+        self.index_value(target_val, index_val).map_err(|err| Box::new(err.at(span)))
+    }
+    
+    fn eval_binary(&mut self, left: &Expr, op: BinaryOp, right: &Expr, span: Span) -> Result<Value, Box<RuntimeError>> {
+        let left_val = self.eval_expr(left)?;
+        // && and || only evaluate the right side when the left doesn't decide,
+        // and give back whichever operand decided the result
+        match op {
+            BinaryOp::And if !left_val.is_truthy() => return Ok(left_val),
+            BinaryOp::Or if left_val.is_truthy() => return Ok(left_val),
+            BinaryOp::And | BinaryOp::Or => return self.eval_expr(right),
+            _ => {}
+        }
+        let right_val = self.eval_expr(right)?;
+        self.eval_binary_op(left_val, op, right_val).map_err(|err| Box::new(err.at(span)))
+    }
+    
+    fn eval_unary(&mut self, op: UnaryOp, expr: &Expr, span: Span) -> Result<Value, Box<RuntimeError>> {
+        let val = self.eval_expr(expr)?;
+        self.eval_unary_op(op, val).map_err(|err| Box::new(err.at(span)))
+    }
+    
+    pub(crate) fn eval_unary_op(&self, op: UnaryOp, val: Value) -> Result<Value, RuntimeError> {
+        match (op, val) {
+            (UnaryOp::Not, val) => Ok(Value::Bool(!val.is_truthy())),
+            (UnaryOp::Minus, Value::Int(n)) => Ok(match n.checked_neg() {
+                Some(n) => Value::Int(n),
+                None => Value::from(BigInt::from_i64(n).neg()),
+            }),
+            (UnaryOp::Minus, Value::BigInt(n)) => Ok(Value::from(n.neg())),
+            (UnaryOp::Minus, Value::Float(n)) => Ok(Value::Float(-n)),
+            (UnaryOp::Minus, other) => Err(RuntimeError::new(RuntimeErrorKind::InvalidOperand {
+                op: "-",
+                operand: other.type_name(),
+            })),
+        }
+    }
+    
+    pub(crate) fn eval_binary_op(&self, left: Value, op: BinaryOp, right: Value) -> Result<Value, RuntimeError> {
+        let result = match (&op, &left, &right) {
+            (BinaryOp::Equals, l, r) => Value::Bool(l == r),
+            (BinaryOp::NotEquals, l, r) => Value::Bool(l != r),
+            (BinaryOp::And | BinaryOp::Or, _, _) => unreachable!("&& and || short-circuit in eval_expr"),
+            (BinaryOp::Add, Value::String(l), r) => Value::String(format!("{}{}", l, r)),
+            (BinaryOp::Add, l, Value::String(r)) => Value::String(format!("{}{}", l, r)),
+            (_, Value::Int(l), Value::Int(r)) => return int_op(op, *l, *r),
+            (_, Value::BigInt(l), Value::BigInt(r)) => return big_int_op(op, l, r),
+            (_, Value::BigInt(l), Value::Int(r)) => return big_int_op(op, l, &BigInt::from_i64(*r)),
+            (_, Value::Int(l), Value::BigInt(r)) => return big_int_op(op, &BigInt::from_i64(*l), r),
+            (_, Value::Float(l), Value::Float(r)) => return float_op(op, *l, *r),
+            // Integers and floats are compared exactly, without rounding the integer to a float
+            (
+                BinaryOp::Greater | BinaryOp::Less | BinaryOp::GreaterEq | BinaryOp::LessEq,
+                Value::Int(_) | Value::BigInt(_) | Value::Float(_),
+                Value::Int(_) | Value::BigInt(_) | Value::Float(_),
+            ) => {
+                let ordering = left.compare_number(&right);
+                Value::Bool(ordering.is_some_and(|ordering| compare(op, ordering, Ordering::Equal)))
+            }
+            // Mixing an int with a float gives a float
+            (_, Value::Int(l), Value::Float(r)) => return float_op(op, *l as f64, *r),
+            (_, Value::Float(l), Value::Int(r)) => return float_op(op, *l, *r as f64),
+            (_, Value::BigInt(l), Value::Float(r)) => return float_op(op, l.to_f64(), *r),
+            (_, Value::Float(l), Value::BigInt(r)) => return float_op(op, *l, r.to_f64()),
+            (
+                BinaryOp::Greater | BinaryOp::Less | BinaryOp::GreaterEq | BinaryOp::LessEq,
+                Value::String(l),
+                Value::String(r),
+            ) => Value::Bool(compare(op, l, r)),
+            _ => {
+                return Err(RuntimeError::new(RuntimeErrorKind::InvalidOperands {
+                    op: op.symbol(),
+                    left: left.type_name(),
+                    right: right.type_name(),
+                }));
+            }
+        };
+        Ok(result)
+    }
+}
+
+// Checks that `index` is a valid position in a list or string of the given length
+fn checked_index(index: &Value, length: usize, target: &'static str) -> Result<usize, RuntimeError> {
+    let i = match index.as_int() {
+        Some(n) if n >= 0 => n as usize,
+        _ => {
+            return Err(RuntimeError::new(RuntimeErrorKind::InvalidIndex(index.to_string())));
+        }
+    };
+    if i >= length {
+        return Err(RuntimeError::new(RuntimeErrorKind::IndexOutOfRange { target, index: i, length }));
+    }
+    Ok(i)
+}
+
+fn int_op(op: BinaryOp, l: i64, r: i64) -> Result<Value, RuntimeError> {
+    let result = match op {
+        BinaryOp::Add => l.checked_add(r),
+        BinaryOp::Sub => l.checked_sub(r),
+        BinaryOp::Mul => l.checked_mul(r),
+        BinaryOp::Div | BinaryOp::Mod if r == 0 => {
+            return Err(RuntimeError::new(RuntimeErrorKind::DivisionByZero));
+        }
+        // Division stays an integer when it's exact, like 6 / 3, and gives a float otherwise
+        BinaryOp::Div => match l.checked_rem(r) {
+            Some(0) => l.checked_div(r),
+            Some(_) => return Ok(Value::Float(l as f64 / r as f64)),
+            None => None,
+        },
+        BinaryOp::Mod => l.checked_rem(r),
+        _ => return Ok(Value::Bool(compare(op, l, r))),
+    };
+    match result {
+        Some(n) => Ok(Value::Int(n)),
+        // The result doesn't fit in 64 bits, so work it out with big integers instead
+        None => big_int_op(op, &BigInt::from_i64(l), &BigInt::from_i64(r)),
+    }
+}
+
+fn big_int_op(op: BinaryOp, l: &BigInt, r: &BigInt) -> Result<Value, RuntimeError> {
+    let result = match op {
+        BinaryOp::Add => l.add(r),
+        BinaryOp::Sub => l.sub(r),
+        BinaryOp::Mul => l.mul(r),
+        BinaryOp::Div | BinaryOp::Mod => {
+            let (quotient, remainder) = l
+                .div_rem(r)
+                .ok_or_else(|| RuntimeError::new(RuntimeErrorKind::DivisionByZero))?;
+            match op {
+                BinaryOp::Mod => remainder,
+                _ if remainder.is_zero() => quotient,
+                // Dividing l and r as floats would lose precision, or give NaN when both are
+                // too big for one
+                _ => return Ok(Value::Float(quotient.to_f64() + remainder.fraction_of(r))),
+            }
+        }
+        _ => return Ok(Value::Bool(compare(op, l, r))),
+    };
+    Ok(Value::from(result))
+}
+
+fn float_op(op: BinaryOp, l: f64, r: f64) -> Result<Value, RuntimeError> {
+    let result = match op {
+        BinaryOp::Add => l + r,
+        BinaryOp::Sub => l - r,
+        BinaryOp::Mul => l * r,
+        BinaryOp::Div | BinaryOp::Mod if r == 0.0 => {
+            return Err(RuntimeError::new(RuntimeErrorKind::DivisionByZero));
+        }
+        BinaryOp::Div => l / r,
+        BinaryOp::Mod => l % r,
+        _ => return Ok(Value::Bool(compare(op, l, r))),
+    };
+    Ok(Value::Float(result))
+}
+
+fn compare<T: PartialOrd>(op: BinaryOp, l: T, r: T) -> bool {
+    match op {
+        BinaryOp::Greater => l > r,
+        BinaryOp::Less => l < r,
+        BinaryOp::GreaterEq => l >= r,
+        BinaryOp::LessEq => l <= r,
+        _ => unreachable!("'{}' is not a comparison", op.symbol()),
+    }
+}
+
+fn output_error(err: io::Error) -> RuntimeError {
+    RuntimeError::new(RuntimeErrorKind::Custom(format!("Failed to write output: {}", err)))
+}
+
+// Records a call in the stack trace of an error raised inside the called function
+fn called_from(mut err: Box<RuntimeError>, function: &str, call_site: Span) -> Box<RuntimeError> {
+    err.stack.push(StackFrame { function: function.to_string(), call_site });
+    err
+}
+
+fn eval_literal(kind: &ExprKind) -> Value {
+    match kind {
+        ExprKind::Int(n) => Value::Int(*n),
+        ExprKind::BigInt(n) => Value::from(n.clone()),
+        ExprKind::Float(n) => Value::Float(*n),
+        ExprKind::String(s) => Value::String(s.clone()),
+        ExprKind::Bool(b) => Value::Bool(*b),
+        ExprKind::Nil => Value::Nil,
+        _ => unreachable!("not a literal: {:?}", kind),
+    }
+}
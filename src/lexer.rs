@@ -0,0 +1,708 @@
+use std::fmt;
+
+use crate::ast::MAX_NESTING;
+use crate::bigint::BigInt;
+
+/// Token types for the language
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    // Keywords
+    Hayde,        // let/var declaration
+    Hiyye,        // assignment operator
+    OngNoCap,     // true
+    Cap,          // false
+    Eza,          // if
+    Betshil,      // condition check
+    Lakan,        // then
+    Walla,        // else
+    Deal,         // end/close block
+    ThreeMol,     // print (3mol -> do/make)
+    Highkey,      // emphasize/print keyword
+    Lowkey,       // quiet/whisper (for debugging)
+    Khalas,       // while loop (khalas -> done/finished when condition met)
+    Yalla,        // continue (let's go!)
+    Waqif,        // break (stop!)
+    Dala,         // function declaration (dala -> function)
+    Rajje3,       // return (rajje3 -> give it back)
+    Walashi,      // nil (walashi -> nothing)
+    
+    // Identifiers and literals
+    Identifier(String),
+    String(String),
+    Int(i64),
+    BigInt(BigInt),
+    Float(f64),
+    InterpolatedString(Vec<StringPart>),
+    
+    // Operators
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,      // modulo
+    
+    // Comparison
+    EqualEqual,   // ==
+    NotEquals,    // !=
+    Greater,      // >
+    Less,         // <
+    GreaterEq,    // >=
+    LessEq,       // <=
+    
+    // Logical
+    And,          // &&
+    Or,           // ||
+    Not,          // !
+    
+    // Delimiters
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    LeftBrace,
+    RightBrace,
+    Comma,
+    Colon,
+    Newline,
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keyword = match self {
+            Token::Identifier(name) => return write!(f, "identifier '{}'", name),
+            Token::String(s) => return write!(f, "string {:?}", s),
+            Token::InterpolatedString(_) => return write!(f, "interpolated string"),
+            Token::Int(n) => return write!(f, "number {}", n),
+            Token::BigInt(n) => return write!(f, "number {}", n),
+            Token::Float(n) => return write!(f, "number {:?}", n),
+            Token::Newline => return write!(f, "end of line"),
+            Token::Eof => return write!(f, "end of file"),
+            Token::Hayde => "hayde",
+            Token::Hiyye => "hiyye",
+            Token::OngNoCap => "ong_no_cap",
+            Token::Cap => "cap",
+            Token::Eza => "eza",
+            Token::Betshil => "betshil",
+            Token::Lakan => "lakan",
+            Token::Walla => "walla",
+            Token::Deal => "deal",
+            Token::ThreeMol => "3mol",
+            Token::Highkey => "highkey",
+            Token::Lowkey => "lowkey",
+            Token::Khalas => "khalas",
+            Token::Yalla => "yalla",
+            Token::Waqif => "waqif",
+            Token::Dala => "dala",
+            Token::Rajje3 => "rajje3",
+            Token::Walashi => "walashi",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Star => "*",
+            Token::Slash => "/",
+            Token::Percent => "%",
+            Token::EqualEqual => "==",
+            Token::NotEquals => "!=",
+            Token::Greater => ">",
+            Token::Less => "<",
+            Token::GreaterEq => ">=",
+            Token::LessEq => "<=",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::Not => "!",
+            Token::LeftParen => "(",
+            Token::RightParen => ")",
+            Token::LeftBracket => "[",
+            Token::RightBracket => "]",
+            Token::LeftBrace => "{",
+            Token::RightBrace => "}",
+            Token::Comma => ",",
+            Token::Colon => ":",
+        };
+        write!(f, "'{}'", keyword)
+    }
+}
+
+/// Piece of an interpolated string: plain text, or the tokens of an embedded
+/// expression followed by the '}' that closed it
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Literal(String),
+    Code(Vec<SpannedToken>),
+}
+
+/// Location of a piece of source code
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: usize,  // byte offset of the first character
+    pub end: usize,    // byte offset just past the last character
+    pub line: usize,   // 1-based line of the first character
+    pub column: usize, // 1-based column of the first character
+}
+
+impl Span {
+    // Span covering both self and other
+    pub(crate) fn to(self, other: Span) -> Span {
+        if other.end <= self.start {
+            return self;
+        }
+        Span { end: other.end, ..self }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{} [{}..{}]", self.line, self.column, self.start, self.end)
+    }
+}
+
+/// Error produced when the source contains something that isn't a valid token
+#[derive(Debug, Clone)]
+pub struct LexError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Lex error at {}: {}", self.span, self.message)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+/// A token together with where it appeared in the source
+#[derive(Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl fmt::Debug for SpannedToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}@{}:{}", self.token, self.span.line, self.span.column)
+    }
+}
+
+// Lexer to tokenize input
+pub(crate) struct Lexer {
+    input: Vec<char>,
+    position: usize,
+    offset: usize, // byte offset of `position`
+    line: usize,
+    column: usize,
+    // Interpolated strings around the code being lexed
+    depth: usize,
+}
+
+impl Lexer {
+    pub(crate) fn new(input: &str) -> Self {
+        Lexer {
+            input: input.chars().collect(),
+            position: 0,
+            offset: 0,
+            line: 1,
+            column: 1,
+            depth: 0,
+        }
+    }
+    
+    fn current_char(&self) -> Option<char> {
+        if self.position < self.input.len() {
+            Some(self.input[self.position])
+        } else {
+            None
+        }
+    }
+    
+    fn peek_char(&self, offset: usize) -> Option<char> {
+        let pos = self.position + offset;
+        if pos < self.input.len() {
+            Some(self.input[pos])
+        } else {
+            None
+        }
+    }
+    
+    fn advance(&mut self) {
+        if let Some(ch) = self.current_char() {
+            self.offset += ch.len_utf8();
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.position += 1;
+    }
+    
+    // Newlines are tokens, so they aren't skipped; a byte order mark counts as whitespace
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.current_char() {
+            if ch != '\n' && (ch.is_whitespace() || ch == '\u{feff}') {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+    }
+    
+    fn skip_comment(&mut self) {
+        if self.current_char() == Some('/') && self.peek_char(1) == Some('/') {
+            while self.current_char().is_some() && self.current_char() != Some('\n') {
+                self.advance();
+            }
+        }
+    }
+    
+    // Zero-width span at the current position
+    fn here(&self) -> Span {
+        Span { start: self.offset, end: self.offset, line: self.line, column: self.column }
+    }
+    
+    // Error covering the source from `start` up to the current position
+    fn error_from(&self, start: Span, message: impl Into<String>) -> LexError {
+        LexError {
+            message: message.into(),
+            span: Span { end: self.offset, ..start },
+        }
+    }
+    
+    // Reads a string literal. One containing `{...}` becomes an interpolated string,
+    // with the tokens of each embedded expression lexed in place.
+    fn read_string(&mut self) -> Result<Token, LexError> {
+        let start = self.here();
+        self.advance(); // skip opening quote
+        let mut result = String::new();
+        let mut parts = Vec::new();
+        
+        loop {
+            match self.current_char() {
+                None => return Err(self.error_from(start, "Unterminated string: missing closing '\"'")),
+                Some('"') => {
+                    self.advance(); // skip closing quote
+                    break;
+                }
+                Some('\\') => {
+                    let escape_start = self.here();
+                    self.advance();
+                    let escaped = self.read_escape().map_err(|message| self.error_from(escape_start, message))?;
+                    result.push(escaped);
+                }
+                Some('{') => {
+                    if !result.is_empty() {
+                        parts.push(StringPart::Literal(std::mem::take(&mut result)));
+                    }
+                    parts.push(StringPart::Code(self.read_interpolation()?));
+                }
+                Some(ch) => {
+                    result.push(ch);
+                    self.advance();
+                }
+            }
+        }
+        
+        if parts.is_empty() {
+            return Ok(Token::String(result));
+        }
+        if !result.is_empty() {
+            parts.push(StringPart::Literal(result));
+        }
+        Ok(Token::InterpolatedString(parts))
+    }
+    
+    // Lexes the expression inside a string's `{...}`, up to and including the closing '}'
+    fn read_interpolation(&mut self) -> Result<Vec<SpannedToken>, LexError> {
+        let start = self.here();
+        if self.depth >= MAX_NESTING {
+            return Err(self.error_from(
+                start,
+                format!("Nesting too deep: strings can be interpolated at most {} levels deep", MAX_NESTING),
+            ));
+        }
+        self.depth += 1;
+        self.advance(); // skip '{'
+        let mut tokens = Vec::new();
+        // Braces of map literals inside the expression
+        let mut depth = 0;
+        
+        loop {
+            let token = self.next_token()?;
+            match token.token {
+                Token::LeftBrace => depth += 1,
+                Token::RightBrace if depth > 0 => depth -= 1,
+                Token::RightBrace => {
+                    tokens.push(token);
+                    self.depth -= 1;
+                    return Ok(tokens);
+                }
+                Token::Newline | Token::Eof => {
+                    return Err(self.error_from(
+                        start,
+                        "Unterminated '{' in string: missing closing '}' (write '\\{' for a literal brace)",
+                    ));
+                }
+                _ => {}
+            }
+            tokens.push(token);
+        }
+    }
+    
+    // Reads the rest of an escape sequence after its backslash
+    fn read_escape(&mut self) -> Result<char, String> {
+        let Some(ch) = self.current_char() else {
+            return Err("Unterminated string: missing closing '\"'".to_string());
+        };
+        self.advance();
+        let escaped = match ch {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            '{' => '{',
+            '}' => '}',
+            'u' => return self.read_unicode_escape(),
+            '\n' => return Err("Unknown escape sequence: '\\' at the end of a line in string".to_string()),
+            other => return Err(format!("Unknown escape sequence '\\{}' in string", other)),
+        };
+        Ok(escaped)
+    }
+    
+    // Reads the `{...}` of a `\u{...}` escape: one to six hex digits naming a code point
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        let invalid = |digits: &str| format!("Invalid unicode escape '\\u{{{}}}' in string", digits);
+        if self.current_char() != Some('{') {
+            return Err("Unicode escapes are written '\\u{...}', e.g. '\\u{e9}'".to_string());
+        }
+        self.advance();
+        
+        let mut digits = String::new();
+        while let Some(ch) = self.current_char() {
+            if ch == '}' || ch == '"' || ch == '\n' {
+                break;
+            }
+            digits.push(ch);
+            self.advance();
+        }
+        if self.current_char() != Some('}') {
+            return Err(format!("Unicode escape '\\u{{{}' is missing its closing '}}'", digits));
+        }
+        self.advance();
+        
+        if digits.is_empty() || digits.len() > 6 {
+            return Err(invalid(&digits));
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| invalid(&digits))
+    }
+    
+    // Reads everything that could belong to a number literal, so that malformed ones
+    // like `1.2.3` or `0xfg` are reported whole instead of splitting into several tokens
+    fn read_number(&mut self) -> Result<Token, LexError> {
+        let start = self.here();
+        let mut literal = String::new();
+        
+        while let Some(ch) = self.current_char() {
+            let radix_prefixed = literal.len() >= 2 && radix_prefix(&literal).is_some();
+            let exponent_sign = (ch == '+' || ch == '-')
+                && !radix_prefixed
+                && literal.ends_with(['e', 'E'])
+                && self.peek_char(1).is_some_and(|next| next.is_ascii_digit());
+            if ch.is_alphanumeric() || ch == '_' || ch == '.' || exponent_sign {
+                literal.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        
+        parse_number(&literal).map_err(|message| self.error_from(start, message))
+    }
+    
+    fn read_identifier(&mut self) -> String {
+        let mut ident = String::new();
+        
+        while let Some(ch) = self.current_char() {
+            if ch.is_alphanumeric() || ch == '_' {
+                ident.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        
+        ident
+    }
+    
+    fn next_token(&mut self) -> Result<SpannedToken, LexError> {
+        self.skip_whitespace();
+        self.skip_comment();
+        
+        let start = self.here();
+        let token = self.scan_token(start)?;
+        let span = Span { end: self.offset, ..start };
+        Ok(SpannedToken { token, span })
+    }
+    
+    // Reads the token starting at `start`, the current position
+    fn scan_token(&mut self, start: Span) -> Result<Token, LexError> {
+        let token = match self.current_char() {
+            None => Token::Eof,
+            Some('\n') => {
+                self.advance();
+                Token::Newline
+            }
+            Some('"') => self.read_string()?,
+            Some('(') => {
+                self.advance();
+                Token::LeftParen
+            }
+            Some(')') => {
+                self.advance();
+                Token::RightParen
+            }
+            Some('[') => {
+                self.advance();
+                Token::LeftBracket
+            }
+            Some(']') => {
+                self.advance();
+                Token::RightBracket
+            }
+            Some('{') => {
+                self.advance();
+                Token::LeftBrace
+            }
+            Some('}') => {
+                self.advance();
+                Token::RightBrace
+            }
+            Some(',') => {
+                self.advance();
+                Token::Comma
+            }
+            Some(':') => {
+                self.advance();
+                Token::Colon
+            }
+            Some('+') => {
+                self.advance();
+                Token::Plus
+            }
+            Some('-') => {
+                self.advance();
+                Token::Minus
+            }
+            Some('*') => {
+                self.advance();
+                Token::Star
+            }
+            Some('/') => {
+                self.advance();
+                Token::Slash
+            }
+            Some('%') => {
+                self.advance();
+                Token::Percent
+            }
+            Some('=') => {
+                self.advance();
+                if self.current_char() == Some('=') {
+                    self.advance();
+                    Token::EqualEqual
+                } else {
+                    Token::Hiyye
+                }
+            }
+            Some('!') => {
+                self.advance();
+                if self.current_char() == Some('=') {
+                    self.advance();
+                    Token::NotEquals
+                } else {
+                    Token::Not
+                }
+            }
+            Some('>') => {
+                self.advance();
+                if self.current_char() == Some('=') {
+                    self.advance();
+                    Token::GreaterEq
+                } else {
+                    Token::Greater
+                }
+            }
+            Some('<') => {
+                self.advance();
+                if self.current_char() == Some('=') {
+                    self.advance();
+                    Token::LessEq
+                } else {
+                    Token::Less
+                }
+            }
+            Some('&') => {
+                self.advance();
+                if self.current_char() == Some('&') {
+                    self.advance();
+                    Token::And
+                } else {
+                    return Err(self.error_from(start, "Unexpected character '&'; use '&&' for 'and'"));
+                }
+            }
+            Some('|') => {
+                self.advance();
+                if self.current_char() == Some('|') {
+                    self.advance();
+                    Token::Or
+                } else {
+                    return Err(self.error_from(start, "Unexpected character '|'; use '||' for 'or'"));
+                }
+            }
+            Some('3') if self.peek_char(1) == Some('m') && self.peek_char(2) == Some('o') && self.peek_char(3) == Some('l') => {
+                // Special case for 3mol keyword
+                self.advance(); // '3'
+                self.advance(); // 'm'
+                self.advance(); // 'o'
+                self.advance(); // 'l'
+                Token::ThreeMol
+            }
+            Some(ch) if ch.is_ascii_digit() => self.read_number()?,
+            Some(ch) if ch.is_alphabetic() || ch == '_' => {
+                let ident = self.read_identifier();
+                match ident.as_str() {
+                    "hayde" => Token::Hayde,
+                    "hiyye" => Token::Hiyye,
+                    "ong_no_cap" => Token::OngNoCap,
+                    "cap" => Token::Cap,
+                    "eza" => Token::Eza,
+                    "betshil" => Token::Betshil,
+                    "lakan" => Token::Lakan,
+                    "walla" => Token::Walla,
+                    "deal" => Token::Deal,
+                    "highkey" => Token::Highkey,
+                    "lowkey" => Token::Lowkey,
+                    "khalas" => Token::Khalas,
+                    "yalla" => Token::Yalla,
+                    "waqif" => Token::Waqif,
+                    "dala" => Token::Dala,
+                    "rajje3" => Token::Rajje3,
+                    "walashi" => Token::Walashi,
+                    _ => Token::Identifier(ident),
+                }
+            }
+            Some(ch) => {
+                self.advance();
+                return Err(self.error_from(start, format!("Unexpected character {:?}", ch)));
+            }
+        };
+        
+        Ok(token)
+    }
+    
+    pub(crate) fn tokenize(&mut self) -> Result<Vec<SpannedToken>, LexError> {
+        let mut tokens = Vec::new();
+        // Open brackets: a newline inside (), [] or {} doesn't end the statement
+        let mut depth = 0usize;
+        
+        loop {
+            let token = self.next_token()?;
+            match token.token {
+                Token::Eof => {
+                    tokens.push(token);
+                    break;
+                }
+                Token::LeftParen | Token::LeftBracket | Token::LeftBrace => depth += 1,
+                Token::RightParen | Token::RightBracket | Token::RightBrace => depth = depth.saturating_sub(1),
+                Token::Newline if depth > 0 => continue,
+                _ => {}
+            }
+            tokens.push(token);
+        }
+        
+        Ok(tokens)
+    }
+}
+
+// Radix and name of a literal starting with 0x, 0b or 0o
+fn radix_prefix(literal: &str) -> Option<(u32, &'static str)> {
+    match literal.get(..2)? {
+        "0x" | "0X" => Some((16, "hexadecimal")),
+        "0b" | "0B" => Some((2, "binary")),
+        "0o" | "0O" => Some((8, "octal")),
+        _ => None,
+    }
+}
+
+// Turns the text of a number literal into a token: decimal digits with an optional
+// fraction and exponent, or an integer in hexadecimal, binary or octal. A '.' or an
+// exponent makes a float; integers too big for an i64 become big integers.
+fn parse_number(literal: &str) -> Result<Token, String> {
+    let malformed = || format!("Malformed number literal '{}'", literal);
+    
+    if let Some((radix, name)) = radix_prefix(literal) {
+        let digits = remove_separators(&literal[2..], radix, literal)?;
+        if digits.is_empty() {
+            return Err(format!("Number literal '{}' has no digits after its prefix", literal));
+        }
+        if let Some(bad) = digits.chars().find(|ch| !ch.is_digit(radix)) {
+            return Err(format!("Invalid digit '{}' in {} literal '{}'", bad, name, literal));
+        }
+        return integer_token(&digits, radix).ok_or_else(malformed);
+    }
+    
+    let digits = remove_separators(literal, 10, literal)?;
+    let (mantissa, exponent) = match digits.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (digits.as_str(), None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let exponent_ok = exponent.is_none_or(|e| all_digits(e.strip_prefix(['+', '-']).unwrap_or(e)));
+    if !all_digits(whole) || !fraction.is_none_or(all_digits) || !exponent_ok {
+        return Err(malformed());
+    }
+    
+    if fraction.is_none() && exponent.is_none() {
+        return integer_token(whole, 10).ok_or_else(malformed);
+    }
+    let n: f64 = digits.parse().map_err(|_| malformed())?;
+    if n.is_infinite() {
+        return Err(format!("Number literal '{}' is too large", literal));
+    }
+    Ok(Token::Float(n))
+}
+
+fn integer_token(digits: &str, radix: u32) -> Option<Token> {
+    match i64::from_str_radix(digits, radix) {
+        Ok(n) => Some(Token::Int(n)),
+        Err(_) => BigInt::parse_radix(digits, radix).map(Token::BigInt),
+    }
+}
+
+// Drops the '_' separators from a literal's digits, each of which must sit between two digits
+fn remove_separators(digits: &str, radix: u32, literal: &str) -> Result<String, String> {
+    let chars: Vec<char> = digits.chars().collect();
+    let is_digit = |i: usize| chars.get(i).is_some_and(|ch| ch.is_digit(radix));
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch == '_' && !(i > 0 && is_digit(i - 1) && is_digit(i + 1)) {
+            return Err(format!("'_' must be between two digits in number literal '{}'", literal));
+        }
+    }
+    Ok(digits.replace('_', ""))
+}
@@ -0,0 +1,58 @@
+//! Faysal Lang: a small scripting language with Lebanese-inspired keywords.
+//!
+//! Create an [`Interpreter`], hand it source code with [`Interpreter::eval`],
+//! and exchange data with the program through its global variables.
+
+pub mod ast;
+mod bigint;
+mod compiler;
+mod error;
+mod files;
+mod interpreter;
+mod lexer;
+mod math;
+mod native;
+mod output;
+mod parser;
+mod random;
+mod resolver;
+mod stack;
+mod strings;
+mod system;
+mod table;
+mod value;
+mod vm;
+
+pub use bigint::BigInt;
+pub use error::{Error, RuntimeError, RuntimeErrorKind, StackFrame};
+pub use interpreter::{Backend, Interpreter};
+pub use lexer::{LexError, Span, SpannedToken, StringPart, Token};
+pub use native::NativeArgs;
+pub use output::OutputBuffer;
+pub use parser::ParseError;
+pub use resolver::{ResolveError, ResolveErrorKind, Warning, WarningKind};
+pub use value::Value;
+
+use ast::Statement;
+use compiler::Compiler;
+use lexer::Lexer;
+use parser::Parser;
+use table::Table;
+
+/// Splits source code into tokens
+pub fn tokenize(source: &str) -> Result<Vec<SpannedToken>, Error> {
+    Ok(Lexer::new(source).tokenize()?)
+}
+
+/// Parses source code into a program ready for `Interpreter::run`
+pub fn parse(source: &str) -> Result<Vec<Statement>, Error> {
+    let mut parser = Parser::new(tokenize(source)?);
+    Ok(parser.parse()?)
+}
+
+/// Bytecode listing of a program as the VM backend would run it
+pub fn disassemble(program: &[Statement]) -> String {
+    let (mut globals, mut functions) = (Table::default(), Table::default());
+    let script = Compiler::compile_script(program, &mut globals, &mut functions);
+    script.listing(&globals, &functions).to_string()
+}
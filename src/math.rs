@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+use std::f64::consts;
+
+use crate::bigint::BigInt;
+use crate::error::RuntimeError;
+use crate::native::{LibraryFunction, NativeArgs};
+use crate::value::Value;
+
+// Globals every interpreter starts with
+pub(crate) const CONSTANTS: [(&str, f64); 2] = [("PI", consts::PI), ("E", consts::E)];
+
+// Most powerful integer `pow` will compute exactly, in bits
+const MAX_POW_BITS: f64 = 1_000_000.0;
+
+// The math functions scripts can call. Integers stay exact wherever the result is a
+// whole number; everything else works on floats.
+pub(crate) fn lookup(name: &str) -> Option<LibraryFunction> {
+    let function: LibraryFunction = match name {
+        "abs" => abs,
+        "sqrt" => sqrt,
+        "pow" => pow,
+        "floor" => |args| to_integer(args, f64::floor),
+        "ceil" => |args| to_integer(args, f64::ceil),
+        "round" => |args| to_integer(args, f64::round),
+        "min" => |args| extreme(args, Ordering::Less),
+        "max" => |args| extreme(args, Ordering::Greater),
+        "sin" => |args| float_function(args, f64::sin),
+        "cos" => |args| float_function(args, f64::cos),
+        "tan" => |args| float_function(args, f64::tan),
+        "exp" => |args| float_function(args, f64::exp),
+        "log" => log,
+        _ => return None,
+    };
+    Some(function)
+}
+
+fn abs(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    match args.get(0)? {
+        Value::Int(n) => Ok(match n.checked_abs() {
+            Some(n) => Value::Int(n),
+            None => Value::from(BigInt::from_i64(*n).neg()),
+        }),
+        Value::BigInt(n) if *n < BigInt::from_i64(0) => Ok(Value::from(n.neg())),
+        Value::BigInt(n) => Ok(Value::from(n.clone())),
+        Value::Float(n) => Ok(Value::Float(n.abs())),
+        other => Err(args.argument_type("a number", other)),
+    }
+}
+
+fn sqrt(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    let n = args.number(0)?;
+    if n < 0.0 {
+        let n = args.get(0)?;
+        return Err(args.error(format!("can't take the square root of a negative number ({})", n)));
+    }
+    Ok(Value::Float(n.sqrt()))
+}
+
+// pow(base, exponent): exact for an integer base and non-negative integer exponent
+fn pow(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(2)?;
+    let exact_base = match args.get(0)? {
+        Value::Int(n) => Some(BigInt::from_i64(*n)),
+        Value::BigInt(n) => Some(n.clone()),
+        _ => None,
+    };
+    if let (Some(base), Some(exponent)) = (exact_base, args.get(1)?.as_int())
+        && exponent >= 0
+    {
+        return int_pow(args, base, exponent as u64);
+    }
+    
+    let (base, exponent) = (args.number(0)?, args.number(1)?);
+    let result = base.powf(exponent);
+    if result.is_nan() {
+        return Err(args.error(format!("{} to the power of {} is not a real number", base, exponent)));
+    }
+    Ok(Value::Float(result))
+}
+
+fn int_pow(args: &NativeArgs, base: BigInt, mut exponent: u64) -> Result<Value, RuntimeError> {
+    let bits = base.to_f64().abs().log2() * exponent as f64;
+    if bits > MAX_POW_BITS {
+        return Err(args.error("result is too large to compute"));
+    }
+    // Square and multiply
+    let mut result = BigInt::from_i64(1);
+    let mut square = base;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.mul(&square);
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            square = square.mul(&square);
+        }
+    }
+    Ok(Value::from(result))
+}
+
+// floor, ceil and round: integers are returned as they are, floats rounded to an integer
+fn to_integer(args: &NativeArgs, rounding: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    let n = match args.get(0)? {
+        Value::Int(_) | Value::BigInt(_) => return Ok(args.get(0)?.clone()),
+        Value::Float(n) => rounding(*n),
+        other => return Err(args.argument_type("a number", other)),
+    };
+    if !n.is_finite() {
+        return Err(args.error(format!("{:?} can't be rounded to an integer", n)));
+    }
+    if n.abs() < 9.2e18 {
+        return Ok(Value::Int(n as i64));
+    }
+    // Every float this large is a whole number, so its decimal digits parse exactly
+    match BigInt::parse(&format!("{:.0}", n)) {
+        Some(n) => Ok(Value::from(n)),
+        None => Err(args.error(format!("{:?} can't be rounded to an integer", n))),
+    }
+}
+
+// min and max: of the arguments, or of the items of a single list argument
+fn extreme(args: &NativeArgs, wanted: Ordering) -> Result<Value, RuntimeError> {
+    let items = match args.values() {
+        [Value::List(items)] => items.borrow().clone(),
+        values => values.to_vec(),
+    };
+    let Some(first) = items.first() else {
+        return Err(args.error("needs at least one number"));
+    };
+    
+    let mut best = first;
+    for item in &items {
+        if item.as_float().is_none() {
+            return Err(args.argument_type("numbers", item));
+        }
+        // NaN is never picked over another number
+        if item.compare_number(best) == Some(wanted) {
+            best = item;
+        }
+    }
+    Ok(best.clone())
+}
+
+fn float_function(args: &NativeArgs, function: fn(f64) -> f64) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    Ok(Value::Float(function(args.number(0)?)))
+}
+
+// log(x): natural logarithm; log(x, base) in the given base
+fn log(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    if args.len() != 1 {
+        args.expect_arity(2)?;
+    }
+    let n = args.number(0)?;
+    if n <= 0.0 {
+        let n = args.get(0)?;
+        return Err(args.error(format!("logarithm is only defined for positive numbers, got {}", n)));
+    }
+    if args.len() == 1 {
+        return Ok(Value::Float(n.ln()));
+    }
+    let base = args.number(1)?;
+    if base <= 0.0 || base == 1.0 {
+        let base = args.get(1)?;
+        return Err(args.error(format!("logarithm base must be positive and not 1, got {}", base)));
+    }
+    // The dedicated functions are exact for powers of their base
+    let result = if base == 2.0 {
+        n.log2()
+    } else if base == 10.0 {
+        n.log10()
+    } else {
+        n.log(base)
+    };
+    Ok(Value::Float(result))
+}
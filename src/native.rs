@@ -0,0 +1,121 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::error::{RuntimeError, RuntimeErrorKind};
+use crate::value::Value;
+
+// A host function registered with `Interpreter::register_native`
+pub(crate) type NativeFunction = Rc<dyn Fn(&NativeArgs) -> Result<Value, RuntimeError>>;
+
+// A function of the built-in library (strings, math, ...); these take their
+// arguments the same way native ones do
+pub(crate) type LibraryFunction = fn(&NativeArgs) -> Result<Value, RuntimeError>;
+
+/// Arguments a script passed to a native function; the helpers turn bad calls into runtime errors
+pub struct NativeArgs<'a> {
+    function: &'a str,
+    values: &'a [Value],
+}
+
+impl<'a> NativeArgs<'a> {
+    pub(crate) fn new(function: &'a str, values: &'a [Value]) -> Self {
+        NativeArgs { function, values }
+    }
+    
+    /// Name the function was called by
+    pub fn function(&self) -> &'a str {
+        self.function
+    }
+    
+    pub fn values(&self) -> &'a [Value] {
+        self.values
+    }
+    
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    
+    /// Fails unless exactly `count` arguments were passed
+    pub fn expect_arity(&self, count: usize) -> Result<(), RuntimeError> {
+        if self.values.len() != count {
+            return Err(RuntimeError::new(RuntimeErrorKind::ArityMismatch {
+                function: self.function.to_string(),
+                expected: count,
+                found: self.values.len(),
+            }));
+        }
+        Ok(())
+    }
+    
+    /// The argument at `index`, of any type
+    pub fn get(&self, index: usize) -> Result<&'a Value, RuntimeError> {
+        self.values.get(index).ok_or_else(|| {
+            RuntimeError::new(RuntimeErrorKind::ArityMismatch {
+                function: self.function.to_string(),
+                expected: index + 1,
+                found: self.values.len(),
+            })
+        })
+    }
+    
+    pub fn int(&self, index: usize) -> Result<i64, RuntimeError> {
+        match self.get(index)? {
+            Value::Int(n) => Ok(*n),
+            Value::BigInt(n) => n.to_i64().ok_or_else(|| self.error("integer argument doesn't fit in 64 bits")),
+            other => Err(self.argument_type("an integer", other)),
+        }
+    }
+    
+    /// A number argument as a float; integers are converted
+    pub fn number(&self, index: usize) -> Result<f64, RuntimeError> {
+        let value = self.get(index)?;
+        value.as_float().ok_or_else(|| self.argument_type("a number", value))
+    }
+    
+    pub fn string(&self, index: usize) -> Result<&'a str, RuntimeError> {
+        match self.get(index)? {
+            Value::String(s) => Ok(s),
+            other => Err(self.argument_type("a string", other)),
+        }
+    }
+    
+    pub fn bool(&self, index: usize) -> Result<bool, RuntimeError> {
+        match self.get(index)? {
+            Value::Bool(b) => Ok(*b),
+            other => Err(self.argument_type("a bool", other)),
+        }
+    }
+    
+    pub fn list(&self, index: usize) -> Result<Rc<RefCell<Vec<Value>>>, RuntimeError> {
+        match self.get(index)? {
+            Value::List(items) => Ok(Rc::clone(items)),
+            other => Err(self.argument_type("a list", other)),
+        }
+    }
+    
+    pub fn map(&self, index: usize) -> Result<Rc<RefCell<BTreeMap<String, Value>>>, RuntimeError> {
+        match self.get(index)? {
+            Value::Map(entries) => Ok(Rc::clone(entries)),
+            other => Err(self.argument_type("a map", other)),
+        }
+    }
+    
+    /// Error for an argument of the wrong type, e.g. `argument_type("a positive number", value)`
+    pub fn argument_type(&self, expected: &'static str, found: &Value) -> RuntimeError {
+        RuntimeError::new(RuntimeErrorKind::ArgumentType {
+            function: self.function.to_string(),
+            expected,
+            found: found.type_name(),
+        })
+    }
+    
+    /// Error with a custom message, reported as coming from this function
+    pub fn error(&self, message: impl Into<String>) -> RuntimeError {
+        RuntimeError::new(RuntimeErrorKind::Custom(format!("{}(): {}", self.function, message.into())))
+    }
+}
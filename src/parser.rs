@@ -0,0 +1,630 @@
+use std::fmt;
+
+use crate::ast::{BinaryOp, Expr, ExprKind, MAX_NESTING, Param, Statement, StatementKind, UnaryOp};
+use crate::lexer::{Span, SpannedToken, StringPart, Token};
+
+/// Error produced when the tokens don't form a valid program
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub expected: String,
+    pub found: Token,
+    pub span: Span,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Parse error at {}: expected {}, found {}",
+            self.span, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Parser
+pub(crate) struct Parser {
+    tokens: Vec<SpannedToken>,
+    position: usize,
+    // Blocks and expressions around the code being parsed
+    depth: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(tokens: Vec<SpannedToken>) -> Self {
+        Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+        }
+    }
+    
+    fn current_token(&self) -> &Token {
+        self.peek_token(0)
+    }
+    
+    fn peek_token(&self, offset: usize) -> &Token {
+        let pos = self.position + offset;
+        if pos < self.tokens.len() {
+            &self.tokens[pos].token
+        } else {
+            &Token::Eof
+        }
+    }
+    
+    fn current_span(&self) -> Span {
+        match self.tokens.get(self.position).or(self.tokens.last()) {
+            Some(token) => token.span,
+            None => Span::default(),
+        }
+    }
+    
+    // Span of the most recently consumed token
+    fn previous_span(&self) -> Span {
+        match self.position.checked_sub(1).and_then(|pos| self.tokens.get(pos)) {
+            Some(token) => token.span,
+            None => self.current_span(),
+        }
+    }
+    
+    // Span from `start` up to the end of the most recently consumed token
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous_span())
+    }
+    
+    fn advance(&mut self) {
+        self.position += 1;
+    }
+    
+    fn error(&self, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            expected: expected.into(),
+            found: self.current_token().clone(),
+            span: self.current_span(),
+        }
+    }
+    
+    // Enters one more level of nesting; the caller restores `depth` when it's done. Deeper
+    // code would overflow the stack of the parser or of whatever walks the tree later.
+    fn nest(&mut self) -> Result<(), ParseError> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.error(format!("at most {} levels of nesting (nesting too deep)", MAX_NESTING)));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+    
+    // Consumes the given token or fails with a description of what was expected
+    fn expect(&mut self, token: Token, expected: &str) -> Result<(), ParseError> {
+        if *self.current_token() != token {
+            return Err(self.error(expected));
+        }
+        self.advance();
+        Ok(())
+    }
+    
+    fn expect_identifier(&mut self, expected: &str) -> Result<String, ParseError> {
+        if let Token::Identifier(name) = self.current_token() {
+            let name = name.clone();
+            self.advance();
+            Ok(name)
+        } else {
+            Err(self.error(expected))
+        }
+    }
+    
+    pub(crate) fn parse(&mut self) -> Result<Vec<Statement>, ParseError> {
+        let mut statements = Vec::new();
+        
+        self.skip_newlines();
+        while *self.current_token() != Token::Eof {
+            statements.push(self.parse_statement()?);
+            self.end_statement(&[])?;
+        }
+        
+        Ok(statements)
+    }
+    
+    // Parses statements until one of the given tokens; the terminator itself is left unconsumed
+    fn parse_block(&mut self, terminators: &[Token], closing: &str) -> Result<Vec<Statement>, ParseError> {
+        let mut statements = Vec::new();
+        
+        self.nest()?;
+        self.skip_newlines();
+        while !terminators.contains(self.current_token()) {
+            if *self.current_token() == Token::Eof {
+                return Err(self.error(closing));
+            }
+            statements.push(self.parse_statement()?);
+            self.end_statement(terminators)?;
+        }
+        self.depth -= 1;
+        
+        Ok(statements)
+    }
+    
+    fn skip_newlines(&mut self) {
+        while *self.current_token() == Token::Newline {
+            self.advance();
+        }
+    }
+    
+    // A statement ends at the end of its line, so `3mol highkey a b` isn't two statements;
+    // the end of the file or of the enclosing block, as in `eza x lakan y hiyye 1 deal`, ends it too
+    fn end_statement(&mut self, terminators: &[Token]) -> Result<(), ParseError> {
+        match self.current_token() {
+            Token::Newline => {
+                self.skip_newlines();
+                Ok(())
+            }
+            Token::Eof => Ok(()),
+            token if terminators.contains(token) => Ok(()),
+            _ => Err(self.error("end of line after the statement")),
+        }
+    }
+    
+    fn parse_statement(&mut self) -> Result<Statement, ParseError> {
+        let start = self.current_span();
+        let kind = match self.current_token() {
+            Token::Hayde => self.parse_var_decl()?,
+            Token::ThreeMol => self.parse_print()?,
+            Token::Lowkey => self.parse_debug()?,
+            Token::Eza => self.parse_if()?,
+            Token::Khalas => self.parse_while()?,
+            Token::Dala => self.parse_function()?,
+            Token::Rajje3 => self.parse_return()?,
+            Token::Yalla => {
+                self.advance();
+                StatementKind::Continue
+            }
+            Token::Waqif => {
+                self.advance();
+                StatementKind::Break
+            }
+            Token::Identifier(_) if *self.peek_token(1) == Token::Hiyye => self.parse_assignment()?,
+            token if starts_expression(token) => self.parse_expression_statement()?,
+            _ => return Err(self.error("a statement")),
+        };
+        
+        Ok(Statement::new(kind, self.span_from(start)))
+    }
+    
+    fn parse_var_decl(&mut self) -> Result<StatementKind, ParseError> {
+        self.advance(); // skip 'hayde'
+        
+        let name = self.expect_identifier("a variable name after 'hayde'")?;
+        self.expect(Token::Hiyye, "'hiyye' after the variable name")?;
+        let value = self.parse_or_expr()?;
+        
+        Ok(StatementKind::VarDecl { name, value })
+    }
+    
+    fn parse_assignment(&mut self) -> Result<StatementKind, ParseError> {
+        let name = self.expect_identifier("a variable name")?;
+        self.expect(Token::Hiyye, "'hiyye'")?;
+        let value = self.parse_or_expr()?;
+        
+        Ok(StatementKind::Assignment { name, value })
+    }
+    
+    fn parse_expression_statement(&mut self) -> Result<StatementKind, ParseError> {
+        let expr = self.parse_or_expr()?;
+        
+        if *self.current_token() != Token::Hiyye {
+            return Ok(StatementKind::Expression(expr));
+        }
+        
+        // Only an indexed target like xs[i] can be assigned to here
+        let ExprKind::Index { target, index } = expr.kind else {
+            return Err(self.error("end of statement"));
+        };
+        self.advance(); // skip 'hiyye'
+        let value = self.parse_or_expr()?;
+        
+        Ok(StatementKind::IndexAssign { target: *target, index: *index, value })
+    }
+    
+    fn parse_print(&mut self) -> Result<StatementKind, ParseError> {
+        self.advance(); // skip '3mol'
+        
+        if *self.current_token() == Token::Highkey {
+            self.advance();
+        }
+        
+        let expr = self.parse_or_expr()?;
+        Ok(StatementKind::Print(expr))
+    }
+    
+    fn parse_debug(&mut self) -> Result<StatementKind, ParseError> {
+        self.advance(); // skip 'lowkey'
+        let expr = self.parse_or_expr()?;
+        Ok(StatementKind::Debug(expr))
+    }
+    
+    fn parse_if(&mut self) -> Result<StatementKind, ParseError> {
+        self.advance(); // skip 'eza'
+        
+        if *self.current_token() == Token::Betshil {
+            self.advance();
+        }
+        
+        let condition = self.parse_or_expr()?;
+        self.expect(Token::Lakan, "'lakan' after the condition")?;
+        
+        let closing = "'deal' to close the 'eza' block";
+        let then_body = self.parse_block(&[Token::Deal, Token::Walla], closing)?;
+        
+        let else_body = if *self.current_token() == Token::Walla {
+            self.advance(); // skip 'walla'
+            Some(self.parse_block(&[Token::Deal], closing)?)
+        } else {
+            None
+        };
+        
+        self.expect(Token::Deal, closing)?;
+        
+        Ok(StatementKind::If { condition, then_body, else_body })
+    }
+    
+    fn parse_while(&mut self) -> Result<StatementKind, ParseError> {
+        self.advance(); // skip 'khalas'
+        
+        if *self.current_token() == Token::Betshil {
+            self.advance();
+        }
+        
+        let condition = self.parse_or_expr()?;
+        self.expect(Token::Lakan, "'lakan' after the condition")?;
+        
+        let closing = "'deal' to close the 'khalas' block";
+        let body = self.parse_block(&[Token::Deal], closing)?;
+        self.expect(Token::Deal, closing)?;
+        
+        Ok(StatementKind::While { condition, body })
+    }
+    
+    fn parse_function(&mut self) -> Result<StatementKind, ParseError> {
+        self.advance(); // skip 'dala'
+        
+        let name = self.expect_identifier("a function name after 'dala'")?;
+        self.expect(Token::LeftParen, "'(' after the function name")?;
+        
+        let mut params = Vec::new();
+        if *self.current_token() != Token::RightParen {
+            loop {
+                let span = self.current_span();
+                let name = self.expect_identifier("a parameter name")?;
+                params.push(Param { name, span });
+                if *self.current_token() == Token::Comma {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+        }
+        
+        self.expect(Token::RightParen, "')' after the parameters")?;
+        self.expect(Token::Lakan, "'lakan' before the function body")?;
+        
+        let closing = "'deal' to close the 'dala' block";
+        let body = self.parse_block(&[Token::Deal], closing)?;
+        self.expect(Token::Deal, closing)?;
+        
+        Ok(StatementKind::Function { name, params, body: body.into() })
+    }
+    
+    fn parse_return(&mut self) -> Result<StatementKind, ParseError> {
+        self.advance(); // skip 'rajje3'
+        
+        // A bare 'rajje3' at the end of its line or block returns walashi
+        match self.current_token() {
+            Token::Newline | Token::Deal | Token::Walla | Token::Eof => Ok(StatementKind::Return(None)),
+            _ => {
+                let value = self.parse_or_expr()?;
+                Ok(StatementKind::Return(Some(value)))
+            }
+        }
+    }
+    
+    // Parses a comma-separated expression list up to the given closing token
+    fn parse_expr_list(&mut self, close: Token, expected: &str) -> Result<Vec<Expr>, ParseError> {
+        self.advance(); // skip opening '(' or '['
+        
+        let mut items = Vec::new();
+        if *self.current_token() != close {
+            loop {
+                items.push(self.parse_or_expr()?);
+                if *self.current_token() == Token::Comma {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+        }
+        
+        self.expect(close, expected)?;
+        
+        Ok(items)
+    }
+    
+    fn binary(left: Expr, op: BinaryOp, right: Expr) -> Expr {
+        let span = left.span.to(right.span);
+        Expr::new(
+            ExprKind::Binary {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            },
+            span,
+        )
+    }
+    
+    // Expression parsing with proper precedence. Every expression and every operator in a
+    // chain counts as a level of nesting, since each adds a level to the tree.
+    fn parse_or_expr(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        self.nest()?;
+        let mut left = self.parse_and_expr()?;
+        
+        while *self.current_token() == Token::Or {
+            self.advance();
+            self.nest()?;
+            let right = self.parse_and_expr()?;
+            left = Self::binary(left, BinaryOp::Or, right);
+        }
+        self.depth = depth;
+        
+        Ok(left)
+    }
+    
+    fn parse_and_expr(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.parse_comparison_expr()?;
+        
+        while *self.current_token() == Token::And {
+            self.advance();
+            self.nest()?;
+            let right = self.parse_comparison_expr()?;
+            left = Self::binary(left, BinaryOp::And, right);
+        }
+        self.depth = depth;
+        
+        Ok(left)
+    }
+    
+    fn parse_comparison_expr(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.parse_additive_expr()?;
+        
+        loop {
+            let op = match self.current_token() {
+                Token::EqualEqual => BinaryOp::Equals,
+                Token::NotEquals => BinaryOp::NotEquals,
+                Token::Greater => BinaryOp::Greater,
+                Token::Less => BinaryOp::Less,
+                Token::GreaterEq => BinaryOp::GreaterEq,
+                Token::LessEq => BinaryOp::LessEq,
+                _ => break,
+            };
+            
+            self.advance();
+            self.nest()?;
+            let right = self.parse_additive_expr()?;
+            left = Self::binary(left, op, right);
+        }
+        self.depth = depth;
+        
+        Ok(left)
+    }
+    
+    fn parse_additive_expr(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.parse_multiplicative_expr()?;
+        
+        loop {
+            let op = match self.current_token() {
+                Token::Plus => BinaryOp::Add,
+                Token::Minus => BinaryOp::Sub,
+                _ => break,
+            };
+            
+            self.advance();
+            self.nest()?;
+            let right = self.parse_multiplicative_expr()?;
+            left = Self::binary(left, op, right);
+        }
+        self.depth = depth;
+        
+        Ok(left)
+    }
+    
+    fn parse_multiplicative_expr(&mut self) -> Result<Expr, ParseError> {
+        let depth = self.depth;
+        let mut left = self.parse_unary_expr()?;
+        
+        loop {
+            let op = match self.current_token() {
+                Token::Star => BinaryOp::Mul,
+                Token::Slash => BinaryOp::Div,
+                Token::Percent => BinaryOp::Mod,
+                _ => break,
+            };
+            
+            self.advance();
+            self.nest()?;
+            let right = self.parse_unary_expr()?;
+            left = Self::binary(left, op, right);
+        }
+        self.depth = depth;
+        
+        Ok(left)
+    }
+    
+    fn parse_unary_expr(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current_span();
+        let op = match self.current_token() {
+            Token::Not => UnaryOp::Not,
+            Token::Minus => UnaryOp::Minus,
+            _ => return self.parse_postfix_expr(),
+        };
+        
+        self.advance();
+        self.nest()?;
+        let expr = self.parse_unary_expr()?;
+        self.depth -= 1;
+        Ok(Expr::new(
+            ExprKind::Unary {
+                op,
+                expr: Box::new(expr),
+            },
+            self.span_from(start),
+        ))
+    }
+    
+    fn parse_map_literal(&mut self) -> Result<ExprKind, ParseError> {
+        self.advance(); // skip '{'
+        
+        let mut entries = Vec::new();
+        while *self.current_token() != Token::RightBrace {
+            let key = self.parse_or_expr()?;
+            self.expect(Token::Colon, "':' after the map key")?;
+            let value = self.parse_or_expr()?;
+            entries.push((key, value));
+            
+            if *self.current_token() == Token::Comma {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        
+        self.expect(Token::RightBrace, "'}' to close the map")?;
+        
+        Ok(ExprKind::Map(entries))
+    }
+    
+    // Each `{...}` of an interpolated string is parsed on its own, from the tokens the lexer
+    // collected for it; the text between them becomes string literals
+    fn parse_interpolation(&self, parts: Vec<StringPart>, span: Span) -> Result<Vec<Expr>, ParseError> {
+        parts
+            .into_iter()
+            .map(|part| match part {
+                StringPart::Literal(text) => Ok(Expr::new(ExprKind::String(text), span)),
+                StringPart::Code(tokens) => {
+                    // The nesting around the string still counts inside its braces
+                    let mut parser = Parser { depth: self.depth, ..Parser::new(tokens) };
+                    let expr = parser.parse_or_expr()?;
+                    parser.expect(Token::RightBrace, "'}' after the interpolated expression")?;
+                    Ok(expr)
+                }
+            })
+            .collect()
+    }
+    
+    fn parse_postfix_expr(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current_span();
+        let depth = self.depth;
+        let mut expr = self.parse_primary_expr()?;
+        
+        while *self.current_token() == Token::LeftBracket {
+            self.advance();
+            self.nest()?;
+            let index = self.parse_or_expr()?;
+            self.expect(Token::RightBracket, "']' after the index")?;
+            expr = Expr::new(
+                ExprKind::Index {
+                    target: Box::new(expr),
+                    index: Box::new(index),
+                },
+                self.span_from(start),
+            );
+        }
+        self.depth = depth;
+        
+        Ok(expr)
+    }
+    
+    fn parse_primary_expr(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current_span();
+        let kind = match self.current_token().clone() {
+            Token::Int(n) => {
+                self.advance();
+                ExprKind::Int(n)
+            }
+            Token::BigInt(n) => {
+                self.advance();
+                ExprKind::BigInt(n)
+            }
+            Token::Float(n) => {
+                self.advance();
+                ExprKind::Float(n)
+            }
+            Token::String(s) => {
+                self.advance();
+                ExprKind::String(s)
+            }
+            Token::InterpolatedString(parts) => {
+                self.advance();
+                ExprKind::Interpolation(self.parse_interpolation(parts, start)?)
+            }
+            Token::OngNoCap => {
+                self.advance();
+                ExprKind::Bool(true)
+            }
+            Token::Cap => {
+                self.advance();
+                ExprKind::Bool(false)
+            }
+            Token::Walashi => {
+                self.advance();
+                ExprKind::Nil
+            }
+            Token::Identifier(name) => {
+                self.advance();
+                if *self.current_token() == Token::LeftParen {
+                    let args = self.parse_expr_list(Token::RightParen, "')' after the arguments")?;
+                    ExprKind::Call { name, args }
+                } else {
+                    ExprKind::Identifier(name)
+                }
+            }
+            Token::LeftParen => {
+                self.advance();
+                let expr = self.parse_or_expr()?;
+                self.expect(Token::RightParen, "')' to close the group")?;
+                expr.kind
+            }
+            Token::LeftBracket => {
+                let items = self.parse_expr_list(Token::RightBracket, "']' to close the list")?;
+                ExprKind::List(items)
+            }
+            Token::LeftBrace => self.parse_map_literal()?,
+            _ => return Err(self.error("an expression")),
+        };
+        
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+}
+
+// Whether an expression can begin with `token`, so a statement starting with it is an
+// expression statement like `greet(name)`, or `1 + 2` in the REPL
+fn starts_expression(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Int(_)
+            | Token::BigInt(_)
+            | Token::Float(_)
+            | Token::String(_)
+            | Token::InterpolatedString(_)
+            | Token::OngNoCap
+            | Token::Cap
+            | Token::Walashi
+            | Token::Identifier(_)
+            | Token::LeftParen
+            | Token::LeftBracket
+            | Token::LeftBrace
+            | Token::Not
+            | Token::Minus
+    )
+}
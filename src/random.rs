@@ -0,0 +1,96 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::error::RuntimeError;
+use crate::native::NativeArgs;
+use crate::value::Value;
+
+// A random built-in; draws from the interpreter's generator
+pub(crate) type RandomFunction = fn(&mut Rng, &NativeArgs) -> Result<Value, RuntimeError>;
+
+// Small pseudo-random generator (SplitMix64): the same seed always gives the same numbers
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+    
+    // Seeded differently on every run
+    pub(crate) fn from_entropy() -> Self {
+        // The standard library keys each RandomState randomly
+        Rng::new(RandomState::new().build_hasher().finish())
+    }
+    
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    
+    // Uniform in 0..bound, where bound is at most 2^64
+    fn below(&mut self, bound: u128) -> u64 {
+        if bound > u128::from(u64::MAX) {
+            return self.next_u64();
+        }
+        let bound = bound as u64;
+        // Rejecting the top sliver of values that would favor small results
+        let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+        loop {
+            let n = self.next_u64();
+            if n <= zone {
+                return n % bound;
+            }
+        }
+    }
+    
+    // Uniform in [0, 1)
+    fn float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub(crate) fn lookup(name: &str) -> Option<RandomFunction> {
+    let function: RandomFunction = match name {
+        "random" => random,
+        "random_int" => random_int,
+        "choice" => choice,
+        _ => return None,
+    };
+    Some(function)
+}
+
+// random(): a float from 0 up to, not including, 1
+fn random(rng: &mut Rng, args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(0)?;
+    Ok(Value::Float(rng.float()))
+}
+
+// random_int(min, max): an integer from min to max, both included
+fn random_int(rng: &mut Rng, args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(2)?;
+    let (min, max) = (args.int(0)?, args.int(1)?);
+    if min > max {
+        return Err(args.error(format!("min ({}) is larger than max ({})", min, max)));
+    }
+    let count = (i128::from(max) - i128::from(min) + 1) as u128;
+    let offset = rng.below(count);
+    Ok(Value::Int((i128::from(min) + i128::from(offset)) as i64))
+}
+
+// choice(xs): a random item of a list
+fn choice(rng: &mut Rng, args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    let items = args.list(0)?;
+    let items = items.borrow();
+    if items.is_empty() {
+        return Err(args.error("can't choose from an empty list"));
+    }
+    let index = rng.below(items.len() as u128) as usize;
+    Ok(items[index].clone())
+}
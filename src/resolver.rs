@@ -0,0 +1,383 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::ast::{Expr, ExprKind, MAX_NESTING, Statement, StatementKind};
+use crate::lexer::Span;
+
+/// Mistake the resolver finds before a program runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveErrorKind {
+    UndefinedVariable(String),
+    // The name is declared, but only further down in the same scope
+    UsedBeforeDeclaration(String),
+    UndeclaredAssignment(String),
+    // 'waqif' or 'yalla' with no loop around it in the same function
+    OutsideLoop(&'static str),
+    // Blocks and expressions nested deeper than the interpreter allows
+    NestingTooDeep,
+}
+
+impl fmt::Display for ResolveErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolveErrorKind::UndefinedVariable(name) => write!(f, "Undefined variable '{}'", name),
+            ResolveErrorKind::UsedBeforeDeclaration(name) => {
+                write!(f, "Variable '{}' is used before its 'hayde' declaration", name)
+            }
+            ResolveErrorKind::UndeclaredAssignment(name) => write!(
+                f,
+                "Cannot assign to undeclared variable '{}'; declare it with 'hayde' first",
+                name
+            ),
+            ResolveErrorKind::OutsideLoop(keyword) => {
+                write!(f, "'{}' can only be used inside a 'khalas' loop", keyword)
+            }
+            ResolveErrorKind::NestingTooDeep => {
+                write!(f, "Nesting too deep; code can be nested at most {} levels", MAX_NESTING)
+            }
+        }
+    }
+}
+
+/// Error found by the resolver, which stops the program from running
+#[derive(Debug, Clone)]
+pub struct ResolveError {
+    pub kind: ResolveErrorKind,
+    pub span: Span,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Resolve error at {}: {}", self.span, self.kind)
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/// Suspicious code the resolver reports without stopping the program
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningKind {
+    UnusedVariable(String),
+    UnusedParameter { function: String, name: String },
+    Redeclaration(String),
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WarningKind::UnusedVariable(name) => write!(f, "Variable '{}' is never used", name),
+            WarningKind::UnusedParameter { function, name } => {
+                write!(f, "Parameter '{}' of {}() is never used", name, function)
+            }
+            WarningKind::Redeclaration(name) => write!(
+                f,
+                "Variable '{}' is already declared in this scope; use '{} hiyye ...' to change it",
+                name, name
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub span: Span,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Warning at {}: {}", self.span, self.kind)
+    }
+}
+
+// A variable declared in a function or block
+struct Local {
+    name: String,
+    span: Span,
+    used: bool,
+    // Set for parameters, naming their function
+    function: Option<String>,
+}
+
+#[derive(Default)]
+struct Scope {
+    locals: Vec<Local>,
+    // Names declared directly in this scope that haven't been reached yet
+    pending: HashSet<String>,
+}
+
+// Checks every variable use and assignment against the declarations in scope, mirroring
+// the runtime rules: blocks and functions have their own scopes, top-level variables are
+// globals, and functions see globals but not the locals of the code around them.
+pub(crate) struct Resolver<'a> {
+    // Globals that exist before the program runs (set by the host or an earlier run)
+    existing_globals: HashSet<&'a str>,
+    // Every global the program declares; function bodies may use them before they appear
+    program_globals: HashSet<String>,
+    // Globals declared by the top-level code resolved so far
+    declared_globals: HashSet<String>,
+    // Scopes of the function or block being resolved, innermost last
+    scopes: Vec<Scope>,
+    in_function: bool,
+    // Loops around the code being resolved, within the current function
+    loop_depth: usize,
+    // Blocks and expressions around the code being resolved
+    depth: usize,
+    errors: Vec<ResolveError>,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn resolve(
+        program: &[Statement],
+        existing_globals: impl Iterator<Item = &'a str>,
+    ) -> Result<Vec<Warning>, Vec<ResolveError>> {
+        let mut resolver = Resolver {
+            existing_globals: existing_globals.collect(),
+            program_globals: declared_names(program),
+            declared_globals: HashSet::new(),
+            scopes: Vec::new(),
+            in_function: false,
+            loop_depth: 0,
+            depth: 0,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        };
+        resolver.resolve_statements(program);
+        if resolver.errors.is_empty() {
+            // Unused variables are only found when their scope ends, so restore source order
+            resolver.warnings.sort_by_key(|warning| warning.span.start);
+            Ok(resolver.warnings)
+        } else {
+            Err(resolver.errors)
+        }
+    }
+    
+    fn resolve_statements(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            self.resolve_statement(stmt);
+        }
+    }
+    
+    // Resolves an 'eza' or 'khalas' body in its own scope
+    fn resolve_block(&mut self, statements: &[Statement], span: Span) {
+        if !self.nest(span) {
+            return;
+        }
+        self.begin_scope(statements);
+        self.resolve_statements(statements);
+        self.end_scope();
+        self.depth -= 1;
+    }
+    
+    // Enters one more level of nesting, or reports that the code at `span` is nested too
+    // deep. The parser has the same limit; this catches trees built by the host.
+    fn nest(&mut self, span: Span) -> bool {
+        if self.depth >= MAX_NESTING {
+            self.errors.push(ResolveError { kind: ResolveErrorKind::NestingTooDeep, span });
+            return false;
+        }
+        self.depth += 1;
+        true
+    }
+    
+    fn begin_scope(&mut self, statements: &[Statement]) {
+        self.scopes.push(Scope {
+            locals: Vec::new(),
+            pending: declared_names(statements),
+        });
+    }
+    
+    // Warns about the scope's variables that were never read
+    fn end_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else { return };
+        for local in scope.locals {
+            if local.used || local.name.starts_with('_') {
+                continue;
+            }
+            let kind = match local.function {
+                Some(function) => WarningKind::UnusedParameter { function, name: local.name },
+                None => WarningKind::UnusedVariable(local.name),
+            };
+            self.warnings.push(Warning { kind, span: local.span });
+        }
+    }
+    
+    fn declare(&mut self, name: &str, span: Span, function: Option<&str>) {
+        let Some(scope) = self.scopes.last_mut() else {
+            if !self.declared_globals.insert(name.to_string()) {
+                self.warnings.push(Warning {
+                    kind: WarningKind::Redeclaration(name.to_string()),
+                    span,
+                });
+            }
+            return;
+        };
+        scope.pending.remove(name);
+        if let Some(existing) = scope.locals.iter_mut().find(|local| local.name == name) {
+            // The old variable stays in use under the same name, so only the
+            // redeclaration itself is reported
+            existing.used = true;
+            self.warnings.push(Warning {
+                kind: WarningKind::Redeclaration(name.to_string()),
+                span,
+            });
+            return;
+        }
+        scope.locals.push(Local {
+            name: name.to_string(),
+            span,
+            used: false,
+            function: function.map(str::to_string),
+        });
+    }
+    
+    // Finds the variable `name` refers to, marking a local one as used when it's read
+    fn lookup(&mut self, name: &str, read: bool) -> Result<(), ResolveErrorKind> {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(local) = scope.locals.iter_mut().find(|local| local.name == name) {
+                local.used |= read;
+                return Ok(());
+            }
+        }
+        
+        let is_global = if self.in_function {
+            self.program_globals.contains(name)
+        } else {
+            self.declared_globals.contains(name)
+        };
+        if is_global || self.existing_globals.contains(name) {
+            return Ok(());
+        }
+        
+        let declared_later = self.scopes.iter().any(|scope| scope.pending.contains(name))
+            || (!self.in_function && self.program_globals.contains(name));
+        if declared_later {
+            Err(ResolveErrorKind::UsedBeforeDeclaration(name.to_string()))
+        } else if read {
+            Err(ResolveErrorKind::UndefinedVariable(name.to_string()))
+        } else {
+            Err(ResolveErrorKind::UndeclaredAssignment(name.to_string()))
+        }
+    }
+    
+    fn resolve_statement(&mut self, stmt: &Statement) {
+        let span = stmt.span;
+        match &stmt.kind {
+            StatementKind::VarDecl { name, value } => {
+                // The value is resolved first, so `hayde x hiyye x + 1` reads an outer x
+                self.resolve_expr(value);
+                self.declare(name, span, None);
+            }
+            StatementKind::Assignment { name, value } => {
+                self.resolve_expr(value);
+                if let Err(kind) = self.lookup(name, false) {
+                    self.errors.push(ResolveError { kind, span });
+                }
+            }
+            StatementKind::IndexAssign { target, index, value } => {
+                self.resolve_expr(target);
+                self.resolve_expr(index);
+                self.resolve_expr(value);
+            }
+            StatementKind::Print(expr) | StatementKind::Debug(expr) | StatementKind::Expression(expr) => {
+                self.resolve_expr(expr);
+            }
+            StatementKind::If { condition, then_body, else_body } => {
+                self.resolve_expr(condition);
+                self.resolve_block(then_body, span);
+                if let Some(else_body) = else_body {
+                    self.resolve_block(else_body, span);
+                }
+            }
+            StatementKind::While { condition, body } => {
+                self.resolve_expr(condition);
+                self.loop_depth += 1;
+                self.resolve_block(body, span);
+                self.loop_depth -= 1;
+            }
+            StatementKind::Function { name, params, body } => {
+                // Function bodies don't see the locals of the code around them
+                let enclosing = std::mem::take(&mut self.scopes);
+                let was_in_function = std::mem::replace(&mut self.in_function, true);
+                let enclosing_loops = std::mem::take(&mut self.loop_depth);
+                if self.nest(span) {
+                    self.begin_scope(body);
+                    for param in params {
+                        self.declare(&param.name, param.span, Some(name));
+                    }
+                    self.resolve_statements(body);
+                    self.end_scope();
+                    self.depth -= 1;
+                }
+                self.loop_depth = enclosing_loops;
+                self.in_function = was_in_function;
+                self.scopes = enclosing;
+            }
+            StatementKind::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.resolve_expr(expr);
+                }
+            }
+            StatementKind::Break | StatementKind::Continue if self.loop_depth == 0 => {
+                let keyword = match stmt.kind {
+                    StatementKind::Break => "waqif",
+                    _ => "yalla",
+                };
+                self.errors.push(ResolveError { kind: ResolveErrorKind::OutsideLoop(keyword), span });
+            }
+            StatementKind::Break | StatementKind::Continue => {}
+        }
+    }
+    
+    fn resolve_expr(&mut self, expr: &Expr) {
+        if !self.nest(expr.span) {
+            return;
+        }
+        match &expr.kind {
+            ExprKind::Int(_)
+            | ExprKind::BigInt(_)
+            | ExprKind::Float(_)
+            | ExprKind::String(_)
+            | ExprKind::Bool(_)
+            | ExprKind::Nil => {}
+            ExprKind::Identifier(name) => {
+                if let Err(kind) = self.lookup(name, true) {
+                    self.errors.push(ResolveError { kind, span: expr.span });
+                }
+            }
+            ExprKind::Call { args, .. } | ExprKind::List(args) | ExprKind::Interpolation(args) => {
+                for arg in args {
+                    self.resolve_expr(arg);
+                }
+            }
+            ExprKind::Map(entries) => {
+                for (key, value) in entries {
+                    self.resolve_expr(key);
+                    self.resolve_expr(value);
+                }
+            }
+            ExprKind::Index { target, index } => {
+                self.resolve_expr(target);
+                self.resolve_expr(index);
+            }
+            ExprKind::Binary { left, right, .. } => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            ExprKind::Unary { expr, .. } => self.resolve_expr(expr),
+        }
+        self.depth -= 1;
+    }
+}
+
+// Names declared by 'hayde' directly in a list of statements, not in nested blocks
+fn declared_names(statements: &[Statement]) -> HashSet<String> {
+    statements
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StatementKind::VarDecl { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
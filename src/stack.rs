@@ -0,0 +1,72 @@
+// Where the running thread's stack is, so the tree walker can stop scripts that recurse too
+// deep with an error before they overflow it. Only the operating system knows how big the
+// stack is, and it only tells through its C API. Stacks grow down on every platform here.
+
+// Address of the caller's stack frame
+#[inline(never)]
+pub(crate) fn address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+// Lowest address of the current thread's stack, if the platform reports it
+#[cfg(target_os = "linux")]
+pub(crate) fn end() -> Option<usize> {
+    // Room for a pthread_attr_t on every Linux target
+    #[repr(C, align(16))]
+    struct Attributes([u8; 128]);
+    
+    unsafe extern "C" {
+        fn pthread_self() -> usize;
+        fn pthread_getattr_np(thread: usize, attributes: *mut Attributes) -> i32;
+        fn pthread_attr_getstack(attributes: *const Attributes, start: *mut usize, size: *mut usize) -> i32;
+        fn pthread_attr_destroy(attributes: *mut Attributes) -> i32;
+    }
+    
+    let mut attributes = Attributes([0; 128]);
+    let (mut start, mut size) = (0, 0);
+    // SAFETY: pthread_getattr_np initializes the attributes, which are destroyed again
+    // once the stack has been read from them
+    unsafe {
+        if pthread_getattr_np(pthread_self(), &mut attributes) != 0 {
+            return None;
+        }
+        let found = pthread_attr_getstack(&attributes, &mut start, &mut size) == 0;
+        pthread_attr_destroy(&mut attributes);
+        found.then_some(start)
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+pub(crate) fn end() -> Option<usize> {
+    unsafe extern "C" {
+        fn pthread_self() -> usize;
+        // The highest address of the stack
+        fn pthread_get_stackaddr_np(thread: usize) -> usize;
+        fn pthread_get_stacksize_np(thread: usize) -> usize;
+    }
+    
+    // SAFETY: both only read the description of the current thread
+    unsafe {
+        let thread = pthread_self();
+        pthread_get_stackaddr_np(thread).checked_sub(pthread_get_stacksize_np(thread))
+    }
+}
+
+#[cfg(windows)]
+pub(crate) fn end() -> Option<usize> {
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetCurrentThreadStackLimits(low: *mut usize, high: *mut usize);
+    }
+    
+    let (mut low, mut high) = (0, 0);
+    // SAFETY: fills in the two limits of the current thread's stack
+    unsafe { GetCurrentThreadStackLimits(&mut low, &mut high) };
+    Some(low)
+}
+
+#[cfg(not(any(target_os = "linux", target_vendor = "apple", windows)))]
+pub(crate) fn end() -> Option<usize> {
+    None
+}
@@ -0,0 +1,149 @@
+use crate::bigint::BigInt;
+use crate::error::RuntimeError;
+use crate::native::{LibraryFunction, NativeArgs};
+use crate::value::Value;
+
+// The string functions scripts can call. Positions count characters, not bytes.
+pub(crate) fn lookup(name: &str) -> Option<LibraryFunction> {
+    let function: LibraryFunction = match name {
+        "slice" => slice,
+        "contains" => contains,
+        "find" => find,
+        "replace" => replace,
+        "upper" => upper,
+        "lower" => lower,
+        "trim" => trim,
+        "split" => split,
+        "join" => join,
+        "number" => number,
+        _ => return None,
+    };
+    Some(function)
+}
+
+// slice(s, start, end): characters start up to (not including) end; end defaults to
+// the length. Also works on lists.
+fn slice(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    if args.len() != 2 {
+        args.expect_arity(3)?;
+    }
+    let (length, target) = match args.get(0)? {
+        Value::String(s) => (s.chars().count(), "string"),
+        Value::List(items) => (items.borrow().len(), "list"),
+        other => return Err(args.argument_type("a string or list", other)),
+    };
+    let start = position(args, 1)?;
+    let end = if args.len() == 3 { position(args, 2)? } else { length };
+    if start > end || end > length {
+        return Err(args.error(format!(
+            "range {} to {} is out of bounds for a {} of length {}",
+            start, end, target, length
+        )));
+    }
+    
+    match args.get(0)? {
+        Value::List(items) => Ok(Value::list(items.borrow()[start..end].to_vec())),
+        _ => {
+            let s = args.string(0)?;
+            Ok(Value::String(s.chars().skip(start).take(end - start).collect()))
+        }
+    }
+}
+
+fn contains(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(2)?;
+    Ok(Value::Bool(args.string(0)?.contains(args.string(1)?)))
+}
+
+// find(s, part): position of the first occurrence of part, or walashi
+fn find(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(2)?;
+    let s = args.string(0)?;
+    Ok(match s.find(args.string(1)?) {
+        Some(byte) => Value::Int(s[..byte].chars().count() as i64),
+        None => Value::Nil,
+    })
+}
+
+// replace(s, from, to): every occurrence of from replaced with to
+fn replace(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(3)?;
+    let from = args.string(1)?;
+    if from.is_empty() {
+        return Err(args.error("the text to replace can't be empty"));
+    }
+    Ok(Value::String(args.string(0)?.replace(from, args.string(2)?)))
+}
+
+fn upper(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    Ok(Value::String(args.string(0)?.to_uppercase()))
+}
+
+fn lower(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    Ok(Value::String(args.string(0)?.to_lowercase()))
+}
+
+fn trim(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    Ok(Value::String(args.string(0)?.trim().to_string()))
+}
+
+// split(s, separator): list of the pieces between separators; an empty separator
+// splits s into its characters
+fn split(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(2)?;
+    let (s, separator) = (args.string(0)?, args.string(1)?);
+    let pieces = if separator.is_empty() {
+        s.chars().map(|ch| Value::String(ch.to_string())).collect()
+    } else {
+        s.split(separator).map(Value::from).collect()
+    };
+    Ok(Value::list(pieces))
+}
+
+// join(xs, separator): the items of a list as one string, printed the way '3mol' would
+fn join(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(2)?;
+    let items = args.list(0)?;
+    let separator = args.string(1)?;
+    let joined = items
+        .borrow()
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join(separator);
+    Ok(Value::String(joined))
+}
+
+// number(s): the integer or float written in s, ignoring surrounding whitespace;
+// walashi if s isn't a number. Passes walashi through, so number(input()) is
+// walashi at the end of input.
+fn number(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    if let Value::Nil = args.get(0)? {
+        return Ok(Value::Nil);
+    }
+    let text = args.string(0)?.trim();
+    if let Ok(n) = text.parse::<i64>() {
+        return Ok(Value::Int(n));
+    }
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Ok(BigInt::parse(text.trim_start_matches('+')).map_or(Value::Nil, Value::from));
+    }
+    // Only plain decimal notation, so words like "inf" and "nan" aren't numbers
+    let is_decimal = digits.starts_with(|ch: char| ch.is_ascii_digit() || ch == '.')
+        && digits.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'-' | b'+'));
+    match text.parse::<f64>() {
+        Ok(n) if is_decimal && n.is_finite() => Ok(Value::Float(n)),
+        _ => Ok(Value::Nil),
+    }
+}
+
+// A non-negative integer argument used as a position
+fn position(args: &NativeArgs, index: usize) -> Result<usize, RuntimeError> {
+    let n = args.int(index)?;
+    usize::try_from(n).map_err(|_| args.error(format!("position must not be negative, got {}", n)))
+}
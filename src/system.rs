@@ -0,0 +1,44 @@
+use std::env;
+
+use crate::error::{RuntimeError, RuntimeErrorKind};
+use crate::native::{LibraryFunction, NativeArgs};
+use crate::value::Value;
+
+// The name of the global holding the command-line arguments given to the script
+pub(crate) const ARGS: &str = "ARGS";
+
+// Functions for scripts that run as command-line tools
+pub(crate) fn lookup(name: &str) -> Option<LibraryFunction> {
+    let function: LibraryFunction = match name {
+        "env" => env_var,
+        "exit" => exit,
+        _ => return None,
+    };
+    Some(function)
+}
+
+// env(name): the value of an environment variable, or walashi if it isn't set
+fn env_var(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    args.expect_arity(1)?;
+    let name = args.string(0)?;
+    // No variable can have such a name, and the standard library may panic on one
+    if name.is_empty() || name.contains(['=', '\0']) {
+        return Ok(Value::Nil);
+    }
+    Ok(match env::var_os(name) {
+        Some(value) => Value::String(value.to_string_lossy().into_owned()),
+        None => Value::Nil,
+    })
+}
+
+// exit(code): stops the program with an exit status from 0 to 255; exit() is exit(0)
+fn exit(args: &NativeArgs) -> Result<Value, RuntimeError> {
+    if !args.is_empty() {
+        args.expect_arity(1)?;
+    }
+    let code = if args.is_empty() { 0 } else { args.int(0)? };
+    match u8::try_from(code) {
+        Ok(code) => Err(RuntimeError::new(RuntimeErrorKind::Exit(i32::from(code)))),
+        Err(_) => Err(args.error(format!("exit code must be from 0 to 255, got {}", code))),
+    }
+}
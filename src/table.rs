@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+// Values looked up by name, where every name gets a fixed index the first time it comes
+// up. The compiler turns global and function names into these indexes, so the VM reaches
+// them without hashing; a name keeps its index for as long as the interpreter lives.
+#[derive(Debug)]
+pub(crate) struct Table<T> {
+    indexes: HashMap<String, usize>,
+    names: Vec<String>,
+    // None for names that have been referred to but not defined
+    values: Vec<Option<T>>,
+}
+
+impl<T> Default for Table<T> {
+    fn default() -> Self {
+        Table {
+            indexes: HashMap::new(),
+            names: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<T> Table<T> {
+    // Index of `name`, numbering it if it's new
+    pub(crate) fn index(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.indexes.get(name) {
+            return index;
+        }
+        self.indexes.insert(name.to_string(), self.names.len());
+        self.names.push(name.to_string());
+        self.values.push(None);
+        self.names.len() - 1
+    }
+    
+    pub(crate) fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+    
+    pub(crate) fn at(&self, index: usize) -> Option<&T> {
+        self.values[index].as_ref()
+    }
+    
+    pub(crate) fn at_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.values[index].as_mut()
+    }
+    
+    pub(crate) fn set(&mut self, index: usize, value: T) {
+        self.values[index] = Some(value);
+    }
+    
+    pub(crate) fn get(&self, name: &str) -> Option<&T> {
+        self.at(*self.indexes.get(name)?)
+    }
+    
+    pub(crate) fn get_mut(&mut self, name: &str) -> Option<&mut T> {
+        let index = *self.indexes.get(name)?;
+        self.at_mut(index)
+    }
+    
+    pub(crate) fn insert(&mut self, name: &str, value: T) {
+        let index = self.index(name);
+        self.set(index, value);
+    }
+    
+    // Names that are defined
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.names
+            .iter()
+            .zip(&self.values)
+            .filter(|(_, value)| value.is_some())
+            .map(|(name, _)| name.as_str())
+    }
+}
@@ -0,0 +1,302 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
+
+/// Value types for runtime
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    // For integers outside the i64 range; arithmetic switches between the two as needed.
+    // Build these with `Value::from`, though one holding a smaller number still behaves
+    // like the equal Int.
+    BigInt(BigInt),
+    Float(f64),
+    String(String),
+    Bool(bool),
+    // Lists are shared by reference, so mutating one is visible through every alias
+    List(Rc<RefCell<Vec<Value>>>),
+    // Maps are shared the same way; keys stay sorted so output is stable
+    Map(Rc<RefCell<BTreeMap<String, Value>>>),
+    Nil,
+}
+
+// Identity of a list or map, to notice one that contains itself
+type CollectionId = *const ();
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, &mut Vec::new())
+    }
+}
+
+// Ints and floats are equal when they hold the same number, so 1 == 1.0
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        self.equals(other, &mut Vec::new())
+    }
+}
+
+impl Value {
+    pub fn list(items: Vec<Value>) -> Self {
+        Value::List(Rc::new(RefCell::new(items)))
+    }
+    
+    pub fn map(entries: BTreeMap<String, Value>) -> Self {
+        Value::Map(Rc::new(RefCell::new(entries)))
+    }
+    
+    // Writes the value the way '3mol' prints it. `open` holds the collections being
+    // written around this one, so one that contains itself comes out as [...] or {...}.
+    fn write(&self, f: &mut fmt::Formatter, open: &mut Vec<CollectionId>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::BigInt(n) => write!(f, "{}", n),
+            // Whole floats keep their ".0" so they can't be mistaken for integers
+            Value::Float(n) => write!(f, "{:?}", n),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", if *b { "ong_no_cap" } else { "cap" }),
+            Value::List(items) => {
+                let id = Rc::as_ptr(items) as CollectionId;
+                if open.contains(&id) {
+                    return write!(f, "[...]");
+                }
+                open.push(id);
+                write!(f, "[")?;
+                for (i, item) in items.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write_nested(f, open)?;
+                }
+                open.pop();
+                write!(f, "]")
+            }
+            Value::Map(entries) => {
+                let id = Rc::as_ptr(entries) as CollectionId;
+                if open.contains(&id) {
+                    return write!(f, "{{...}}");
+                }
+                open.push(id);
+                write!(f, "{{")?;
+                for (i, (key, val)) in entries.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}: ", key)?;
+                    val.write_nested(f, open)?;
+                }
+                open.pop();
+                write!(f, "}}")
+            }
+            Value::Nil => write!(f, "walashi"),
+        }
+    }
+    
+    // Strings inside collections are quoted so ["1", 1] stays readable
+    fn write_nested(&self, f: &mut fmt::Formatter, open: &mut Vec<CollectionId>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            other => other.write(f, open),
+        }
+    }
+    
+    // `comparing` holds the pairs of collections being compared around these two. When
+    // collections contain themselves a pair can come up again, and is taken as equal:
+    // any difference between them is found where they are already being compared.
+    fn equals(&self, other: &Value, comparing: &mut Vec<(CollectionId, CollectionId)>) -> bool {
+        match (self, other) {
+            (Value::Int(l), Value::Int(r)) => l == r,
+            (Value::BigInt(l), Value::BigInt(r)) => l == r,
+            (Value::Int(i), Value::BigInt(b)) | (Value::BigInt(b), Value::Int(i)) => b.to_i64() == Some(*i),
+            (Value::Float(l), Value::Float(r)) => l == r,
+            // Compared exactly: 2^53 + 1 is not equal to the float 2^53
+            (Value::Int(_) | Value::BigInt(_), Value::Float(_)) | (Value::Float(_), Value::Int(_) | Value::BigInt(_)) => {
+                self.compare_number(other) == Some(Ordering::Equal)
+            }
+            (Value::String(l), Value::String(r)) => l == r,
+            (Value::Bool(l), Value::Bool(r)) => l == r,
+            (Value::List(l), Value::List(r)) => {
+                let pair = (Rc::as_ptr(l) as CollectionId, Rc::as_ptr(r) as CollectionId);
+                if Rc::ptr_eq(l, r) || comparing.contains(&pair) {
+                    return true;
+                }
+                let (l, r) = (l.borrow(), r.borrow());
+                comparing.push(pair);
+                let equal = l.len() == r.len()
+                    && l.iter().zip(r.iter()).all(|(l, r)| l.equals(r, comparing));
+                comparing.pop();
+                equal
+            }
+            (Value::Map(l), Value::Map(r)) => {
+                let pair = (Rc::as_ptr(l) as CollectionId, Rc::as_ptr(r) as CollectionId);
+                if Rc::ptr_eq(l, r) || comparing.contains(&pair) {
+                    return true;
+                }
+                let (l, r) = (l.borrow(), r.borrow());
+                comparing.push(pair);
+                let equal = l.len() == r.len()
+                    && l.iter()
+                        .zip(r.iter())
+                        .all(|((lk, lv), (rk, rv))| lk == rk && lv.equals(rv, comparing));
+                comparing.pop();
+                equal
+            }
+            (Value::Nil, Value::Nil) => true,
+            _ => false,
+        }
+    }
+    
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) | Value::BigInt(_) => "int",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Nil => "walashi",
+        }
+    }
+    
+    /// The value as an i64 if it's an integer that fits in one
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            Value::BigInt(n) => n.to_i64(),
+            _ => None,
+        }
+    }
+    
+    /// The value as a float if it's a number of either kind
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Int(n) => Some(*n as f64),
+            Value::BigInt(n) => Some(n.to_f64()),
+            Value::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+    
+    /// Orders two numbers exactly: an integer isn't rounded to a float first, so 2^53 + 1
+    /// is greater than the float 2^53. None if either isn't a number or is NaN.
+    pub fn compare_number(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Int(l), Value::Int(r)) => Some(l.cmp(r)),
+            (Value::Float(l), Value::Float(r)) => l.partial_cmp(r),
+            (Value::Int(i), Value::Float(f)) => compare_int_float(*i, *f),
+            (Value::BigInt(b), Value::Float(f)) => compare_big_int_float(b, *f),
+            (Value::Float(_), _) => other.compare_number(self).map(Ordering::reverse),
+            (l, r) => Some(l.as_big_int()?.cmp(&r.as_big_int()?)),
+        }
+    }
+    
+    fn as_big_int(&self) -> Option<BigInt> {
+        match self {
+            Value::Int(n) => Some(BigInt::from_i64(*n)),
+            Value::BigInt(n) => Some(n.clone()),
+            _ => None,
+        }
+    }
+    
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Int(n) => *n != 0,
+            Value::BigInt(n) => !n.is_zero(),
+            Value::Float(n) => *n != 0.0,
+            Value::String(s) => !s.is_empty(),
+            Value::List(items) => !items.borrow().is_empty(),
+            Value::Map(entries) => !entries.borrow().is_empty(),
+            Value::Nil => false,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+// Big integers that fit in an i64 become a plain Int
+impl From<BigInt> for Value {
+    fn from(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(n) => Value::Int(n),
+            None => Value::BigInt(n),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Float(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Self {
+        Value::list(items)
+    }
+}
+
+// An integer and a float are ordered by the float's whole part, and then by its fraction
+fn compare_int_float(i: i64, f: f64) -> Option<Ordering> {
+    if f.is_nan() {
+        return None;
+    }
+    // The cast saturates, so infinities and floats far past the i64 range still order right
+    let whole = f.trunc();
+    Some((i as i128).cmp(&(whole as i128)).then_with(|| fraction_order(f - whole)))
+}
+
+fn compare_big_int_float(b: &BigInt, f: f64) -> Option<Ordering> {
+    if f.is_nan() {
+        return None;
+    }
+    let whole = f.trunc();
+    match BigInt::from_f64(whole) {
+        Some(whole_int) => Some(b.cmp(&whole_int).then_with(|| fraction_order(f - whole))),
+        None if f > 0.0 => Some(Ordering::Less),
+        None => Some(Ordering::Greater),
+    }
+}
+
+// How an integer equal to a float's whole part orders against the float
+fn fraction_order(fraction: f64) -> Ordering {
+    if fraction > 0.0 {
+        Ordering::Less
+    } else if fraction < 0.0 {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
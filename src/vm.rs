@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::ast::UnaryOp;
+use crate::compiler::{FunctionProto, Instruction};
+use crate::error::{RuntimeError, RuntimeErrorKind, StackFrame};
+use crate::interpreter::{Interpreter, MAX_CALL_DEPTH};
+use crate::value::Value;
+
+fn pop(stack: &mut Vec<Value>) -> Value {
+    stack.pop().expect("VM stack underflow")
+}
+
+struct CallFrame {
+    function: Rc<FunctionProto>,
+    ip: usize,
+    // Stack index of the frame's first local slot
+    base: usize,
+}
+
+// Stack-based VM running compiled bytecode; globals, functions and builtins come from the Interpreter
+pub(crate) struct Vm {
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+}
+
+impl Vm {
+    pub(crate) fn new() -> Self {
+        Vm {
+            stack: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+    
+    pub(crate) fn run(&mut self, interpreter: &mut Interpreter, script: Rc<FunctionProto>) -> Result<Value, RuntimeError> {
+        // Slots for variables declared in top-level blocks
+        self.stack.resize(script.local_count, Value::Nil);
+        self.frames.push(CallFrame { function: script, ip: 0, base: 0 });
+        
+        self.execute(interpreter).map_err(|err| self.locate_error(err))
+    }
+    
+    // Attaches the failing instruction's location and the active calls to an error
+    fn locate_error(&self, mut err: RuntimeError) -> RuntimeError {
+        let span_of = |frame: &CallFrame| frame.function.chunk.spans[frame.ip.saturating_sub(1)];
+        if let Some(frame) = self.frames.last() {
+            err = err.at(span_of(frame));
+        }
+        for pair in self.frames.windows(2).rev() {
+            err.stack.push(StackFrame {
+                function: pair[1].function.name.clone(),
+                call_site: span_of(&pair[0]),
+            });
+        }
+        err
+    }
+    
+    fn execute(&mut self, interpreter: &mut Interpreter) -> Result<Value, RuntimeError> {
+        loop {
+            let depth = self.frames.len();
+            let frame = self.frames.last_mut().expect("VM has no active frame");
+            let instruction = frame.function.chunk.code[frame.ip];
+            frame.ip += 1;
+            
+            match instruction {
+                Instruction::Constant(index) => {
+                    let value = frame.function.chunk.constants[index].clone();
+                    self.stack.push(value);
+                }
+                Instruction::Nil => self.stack.push(Value::Nil),
+                Instruction::True => self.stack.push(Value::Bool(true)),
+                Instruction::False => self.stack.push(Value::Bool(false)),
+                Instruction::Pop => {
+                    pop(&mut self.stack);
+                }
+                Instruction::DefineGlobal(index) => {
+                    let value = pop(&mut self.stack);
+                    interpreter.globals.set(index, value);
+                }
+                Instruction::SetGlobal(index) => {
+                    let value = pop(&mut self.stack);
+                    match interpreter.globals.at_mut(index) {
+                        Some(variable) => *variable = value,
+                        None => {
+                            let name = interpreter.globals.name(index).to_string();
+                            return Err(RuntimeError::new(RuntimeErrorKind::UndeclaredAssignment(name)));
+                        }
+                    }
+                }
+                Instruction::GetGlobal(index) => match interpreter.globals.at(index) {
+                    Some(value) => self.stack.push(value.clone()),
+                    None => {
+                        let name = interpreter.globals.name(index).to_string();
+                        return Err(RuntimeError::new(RuntimeErrorKind::UndefinedVariable(name)));
+                    }
+                },
+                Instruction::GetLocal(slot) => {
+                    let value = self.stack[frame.base + slot].clone();
+                    self.stack.push(value);
+                }
+                Instruction::SetLocal(slot) => {
+                    let slot = frame.base + slot;
+                    self.stack[slot] = pop(&mut self.stack);
+                }
+                Instruction::Concat(count) => {
+                    let parts = self.stack.split_off(self.stack.len() - count);
+                    let joined: String = parts.iter().map(Value::to_string).collect();
+                    self.stack.push(Value::String(joined));
+                }
+                Instruction::BuildList(count) => {
+                    let items = self.stack.split_off(self.stack.len() - count);
+                    self.stack.push(Value::list(items));
+                }
+                Instruction::BuildMap(count) => {
+                    let flat = self.stack.split_off(self.stack.len() - count * 2);
+                    let mut map = BTreeMap::new();
+                    let mut flat = flat.into_iter();
+                    while let (Some(key), Some(value)) = (flat.next(), flat.next()) {
+                        map.insert(interpreter.map_key(key)?, value);
+                    }
+                    self.stack.push(Value::map(map));
+                }
+                Instruction::Index => {
+                    let index = pop(&mut self.stack);
+                    let target = pop(&mut self.stack);
+                    let value = interpreter.index_value(target, index)?;
+                    self.stack.push(value);
+                }
+                Instruction::SetIndex => {
+                    let value = pop(&mut self.stack);
+                    let index = pop(&mut self.stack);
+                    let target = pop(&mut self.stack);
+                    interpreter.set_index(target, index, value)?;
+                }
+                Instruction::Binary(op) => {
+                    let right = pop(&mut self.stack);
+                    let left = pop(&mut self.stack);
+                    let value = interpreter.eval_binary_op(left, op, right)?;
+                    self.stack.push(value);
+                }
+                Instruction::Negate => {
+                    let value = pop(&mut self.stack);
+                    let value = interpreter.eval_unary_op(UnaryOp::Minus, value)?;
+                    self.stack.push(value);
+                }
+                Instruction::Not => {
+                    let value = pop(&mut self.stack);
+                    self.stack.push(Value::Bool(!value.is_truthy()));
+                }
+                Instruction::Jump(target) => frame.ip = target,
+                Instruction::JumpIfFalse(target) => {
+                    if !pop(&mut self.stack).is_truthy() {
+                        frame.ip = target;
+                    }
+                }
+                Instruction::JumpIfFalseOrPop(target) => {
+                    if self.stack.last().is_some_and(|value| !value.is_truthy()) {
+                        frame.ip = target;
+                    } else {
+                        self.stack.pop();
+                    }
+                }
+                Instruction::JumpIfTrueOrPop(target) => {
+                    if self.stack.last().is_some_and(|value| value.is_truthy()) {
+                        frame.ip = target;
+                    } else {
+                        self.stack.pop();
+                    }
+                }
+                Instruction::Call(index, argc) => {
+                    match interpreter.compiled_functions.at(index) {
+                        Some(function) => {
+                            if argc != function.arity {
+                                return Err(RuntimeError::new(RuntimeErrorKind::ArityMismatch {
+                                    function: function.name.clone(),
+                                    expected: function.arity,
+                                    found: argc,
+                                }));
+                            }
+                            if depth > MAX_CALL_DEPTH {
+                                return Err(RuntimeError::new(RuntimeErrorKind::StackOverflow(
+                                    MAX_CALL_DEPTH,
+                                )));
+                            }
+                            let function = Rc::clone(function);
+                            let base = self.stack.len() - argc;
+                            self.stack.resize(base + function.local_count, Value::Nil);
+                            self.frames.push(CallFrame { function, ip: 0, base });
+                        }
+                        None => {
+                            let name = interpreter.compiled_functions.name(index).to_string();
+                            let args = self.stack.split_off(self.stack.len() - argc);
+                            let value = interpreter.call_builtin(&name, args)?;
+                            self.stack.push(value);
+                        }
+                    }
+                }
+                Instruction::Return => {
+                    let value = pop(&mut self.stack);
+                    let frame = self.frames.pop().expect("VM has no active frame");
+                    if self.frames.is_empty() {
+                        // Returning from the script ends the program
+                        self.stack.clear();
+                        return Ok(value);
+                    }
+                    self.stack.truncate(frame.base);
+                    self.stack.push(value);
+                }
+                Instruction::Print => {
+                    let value = pop(&mut self.stack);
+                    interpreter.print(&value)?;
+                }
+                Instruction::Debug => {
+                    let value = pop(&mut self.stack);
+                    interpreter.debug(&value)?;
+                }
+                Instruction::DefineFunction(index, table_index) => {
+                    let function = Rc::clone(&frame.function.chunk.functions[index]);
+                    interpreter.compiled_functions.set(table_index, function);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// The tree walker and the VM must behave the same on every program
+use std::fs;
+use std::io::Cursor;
+use std::thread;
+
+use faysallang::ast::{Expr, ExprKind, Statement, StatementKind, UnaryOp};
+use faysallang::{Backend, Error, Interpreter, OutputBuffer, ResolveErrorKind, RuntimeErrorKind, Span, Value};
+
+// Room for the tree walker to reach the call depth limit in debug builds, like the CLI has
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+type Outcome = (String, String, Result<String, String>);
+
+// Output, debug output and outcome of running `source` on one backend
+fn run(source: &str, backend: Backend) -> Outcome {
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, || run_here(source, backend))
+            .unwrap()
+            .join()
+            .unwrap()
+    })
+}
+
+fn run_here(source: &str, backend: Backend) -> Outcome {
+    let mut interpreter = Interpreter::with_backend(backend);
+    let (output, debug_output) = (OutputBuffer::new(), OutputBuffer::new());
+    interpreter.set_output(output.clone());
+    interpreter.set_debug_output(debug_output.clone());
+    interpreter.set_input(Cursor::new("10\n5\n15\n"));
+    interpreter.set_seed(7);
+    let result = interpreter.eval(source).map(|value| value.to_string()).map_err(|err| err.to_string());
+    (output.take(), debug_output.take(), result)
+}
+
+fn assert_same(name: &str, source: &str) {
+    let tree_walker = run(source, Backend::TreeWalker);
+    let vm = run(source, Backend::Vm);
+    assert_eq!(tree_walker, vm, "backends differ on {}", name);
+}
+
+#[test]
+fn examples_run_the_same_on_both_backends() {
+    let mut paths: Vec<_> = fs::read_dir("examples")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "fsl"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let source = fs::read_to_string(&path).unwrap();
+        let (output, _, result) = run(&source, Backend::TreeWalker);
+        assert!(result.is_ok(), "{} failed: {:?}", path.display(), result);
+        assert!(!output.is_empty(), "{} printed nothing", path.display());
+        assert_same(&path.display().to_string(), &source);
+    }
+}
+
+#[test]
+fn edge_cases_run_the_same_on_both_backends() {
+    let cases = [
+        "eza betshil ong_no_cap lakan\n   3mol highkey \"before\"\n   yalla\n   3mol highkey \"after\"\ndeal",
+        "dala f() lakan\n   waqif\ndeal\nf()",
+        "hayde i hiyye 0\nkhalas betshil i < 5 lakan\n   i hiyye i + 1\n   eza betshil i == 2 lakan\n      yalla\n   deal\n   eza betshil i == 4 lakan\n      waqif\n   deal\n   3mol highkey i\ndeal",
+        "dala f(n) lakan\n   khalas betshil ong_no_cap lakan\n      rajje3 n * 2\n   deal\ndeal\nf(21)",
+        "3mol highkey 7 / 2\n3mol highkey 7 % -3\n3mol highkey 9223372036854775807 + 1",
+        "3mol highkey -9223372036854775807 - 1\n3mol highkey 2.5 * 4\n3mol highkey \"a\" + 1",
+        "3mol highkey 1 / 0",
+        "hayde xs hiyye [1, 2]\n3mol highkey xs[5]",
+        "hayde m hiyye {\"a\": 1}\nm[\"b\"] hiyye [m[\"a\"], walashi]\n3mol highkey m\nlowkey keys(m)",
+        "hayde s hiyye \"marhaba\"\n3mol highkey \"{upper(s[0])}{slice(s, 1)}: {len(s)}\"",
+        "dala f(n) lakan\n   rajje3 f(n + 1)\ndeal\nf(0)",
+        "dala g(x) lakan\n   rajje3 x + walashi\ndeal\ndala f() lakan\n   rajje3 g(1)\ndeal\nf()",
+        "3mol highkey cap || 0\n3mol highkey ong_no_cap && \"\"\n3mol highkey !walashi",
+        "3mol highkey [random_int(1, 100), random(), choice([1, 2, 3])]",
+        "3mol highkey number(input(\"> \")) + 1",
+        "exit(3)",
+        "3mol highkey pow(2, 100) == pow(2, 100)\n3mol highkey max([3, 9, 2]) - min(4, 1)",
+    ];
+    for source in cases {
+        assert_same(source, source);
+    }
+}
+
+#[test]
+fn loop_keywords_outside_a_loop_are_rejected() {
+    let cases = [
+        ("eza betshil ong_no_cap lakan\n   yalla\ndeal", "'yalla'"),
+        ("dala f() lakan\n   waqif\ndeal", "'waqif'"),
+        // A function body doesn't belong to the loop it's declared in
+        ("khalas betshil cap lakan\n   dala f() lakan\n      yalla\n   deal\ndeal", "'yalla'"),
+    ];
+    for (source, keyword) in cases {
+        for backend in [Backend::TreeWalker, Backend::Vm] {
+            let (output, _, result) = run(source, backend);
+            let err = result.unwrap_err();
+            assert!(err.starts_with("Resolve error") && err.contains(keyword), "{}", err);
+            assert_eq!(output, "");
+        }
+    }
+}
+
+#[test]
+fn deep_recursion_on_a_small_stack_stops_with_an_error() {
+    // Test threads get 2 MiB of stack, less than the tree walker needs for 1000 calls in
+    // debug builds
+    let source = "dala f(n) lakan\n   rajje3 1 + f(n + 1)\ndeal\nf(0)";
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let mut interpreter = Interpreter::with_backend(backend);
+        match interpreter.eval(source) {
+            Err(Error::Runtime(err)) => {
+                assert!(matches!(err.kind, RuntimeErrorKind::StackOverflow(_)), "{}", err)
+            }
+            other => panic!("expected a stack overflow, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn both_backends_allow_a_thousand_nested_calls() {
+    let plain = "dala f(n) lakan\n   eza n == 0 lakan\n      rajje3 0\n   deal\n   rajje3 1 + f(n - 1)\ndeal\n";
+    let nested = "dala f(n) lakan\n   khalas betshil ong_no_cap lakan\n      eza n > 0 lakan\n         rajje3 1 + f(n - 1)\n      deal\n      rajje3 0\n   deal\ndeal\n";
+    for function in [plain, nested] {
+        for backend in [Backend::TreeWalker, Backend::Vm] {
+            let (_, _, result) = run(&format!("{}f(999)", function), backend);
+            assert_eq!(result, Ok("999".to_string()));
+            let (_, _, result) = run(&format!("{}f(1000)", function), backend);
+            let err = result.unwrap_err();
+            assert!(err.contains("Stack overflow: more than 1000 nested calls"), "{}", err);
+        }
+    }
+}
+
+#[test]
+fn deep_nesting_is_an_error_not_a_crash() {
+    // Far past the limit, on a test thread's 2 MiB stack
+    let n = 10_000;
+    let cases = [
+        format!("3mol highkey {}1{}", "(".repeat(n), ")".repeat(n)),
+        format!("3mol highkey {}1", "-".repeat(n)),
+        format!("3mol highkey {}", vec!["1"; n].join(" + ")),
+        format!("3mol highkey {}1{}", "[".repeat(n), "]".repeat(n)),
+        format!("hayde xs hiyye [1]\n3mol highkey xs{}", "[0]".repeat(n)),
+        format!("3mol highkey {}1{}", "{1: ".repeat(n), "}".repeat(n)),
+        format!("{}3mol highkey 1\n{}", "eza ong_no_cap lakan\n".repeat(n), "deal\n".repeat(n)),
+        format!("3mol highkey {}1{}", "\"{".repeat(n), "}\"".repeat(n)),
+    ];
+    for source in &cases {
+        for backend in [Backend::TreeWalker, Backend::Vm] {
+            let (output, _, result) = run_here(source, backend);
+            let err = result.unwrap_err();
+            assert!(err.contains("nesting too deep") || err.contains("Nesting too deep"), "{}", err);
+            assert_eq!(output, "");
+        }
+    }
+    // Code at the limit still runs
+    let source = format!("3mol highkey {}1{}", "(".repeat(99), ")".repeat(99));
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        assert_eq!(run_here(&source, backend).0, "1\n");
+    }
+}
+
+#[test]
+fn nesting_at_the_limit_in_the_deepest_call_still_fits_the_stack() {
+    // The last call the stack allows still has room for a function body nested as deep as
+    // the parser accepts
+    let source = format!(
+        "dala f(n) lakan\n   rajje3 {}f(n + 1){}\ndeal\nf(0)",
+        "1 + (".repeat(45),
+        ")".repeat(45)
+    );
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let err = run_here(&source, backend).2.unwrap_err();
+        assert!(err.contains("Stack overflow"), "{}", err);
+    }
+}
+
+#[test]
+fn the_resolver_rejects_trees_nested_too_deep() {
+    // Hosts can build a program without the parser, which is where the limit is usually found
+    let span = Span::default();
+    let mut expr = Expr { kind: ExprKind::Int(1), span };
+    for _ in 0..1000 {
+        expr = Expr { kind: ExprKind::Unary { op: UnaryOp::Minus, expr: Box::new(expr) }, span };
+    }
+    let program = vec![Statement { kind: StatementKind::Print(expr), span }];
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let mut interpreter = Interpreter::with_backend(backend);
+        match interpreter.run(program.clone()) {
+            Err(Error::Resolve(errors)) => assert_eq!(errors[0].kind, ResolveErrorKind::NestingTooDeep),
+            other => panic!("expected a resolve error, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn globals_and_functions_persist_between_runs() {
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let mut interpreter = Interpreter::with_backend(backend);
+        interpreter.eval("hayde count hiyye 1\ndala bump(n) lakan\n   count hiyye count + n\n   rajje3 count\ndeal").unwrap();
+        interpreter.set_global("step", 10);
+        assert_eq!(interpreter.eval("bump(step)").unwrap(), Value::Int(11));
+        assert_eq!(interpreter.get_global("count"), Some(Value::Int(11)));
+        // Redefining a function replaces it for the code compiled before too
+        interpreter.eval("dala twice() lakan\n   rajje3 bump(1) + bump(1)\ndeal").unwrap();
+        interpreter.eval("dala bump(n) lakan\n   rajje3 n * 100\ndeal").unwrap();
+        assert_eq!(interpreter.eval("twice()").unwrap(), Value::Int(200));
+        interpreter.set_global("count", "host");
+        assert_eq!(interpreter.eval("count + PI").unwrap(), Value::from("host3.141592653589793"));
+    }
+}
+
+#[test]
+fn collections_that_contain_themselves() {
+    let source = "hayde xs hiyye [1]\npush(xs, xs)\n3mol highkey xs\n3mol highkey xs == xs\n\
+        hayde ys hiyye [1]\npush(ys, ys)\n3mol highkey xs == ys\n\
+        hayde m hiyye {\"a\": 1}\nm[\"k\"] hiyye m\n3mol highkey m\n3mol highkey m == m\n\
+        m[\"l\"] hiyye [m, xs]\n3mol highkey m";
+    let expected = "[1, [...]]\nong_no_cap\nong_no_cap\n{\"a\": 1, \"k\": {...}}\nong_no_cap\n\
+        {\"a\": 1, \"k\": {...}, \"l\": [{...}, [1, [...]]]}\n";
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let (output, _, result) = run(source, backend);
+        assert_eq!(result, Ok("walashi".to_string()));
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn any_expression_can_be_a_statement() {
+    let cases = [
+        ("1 + 2", "3"),
+        ("hayde x hiyye 4\n(x)", "4"),
+        ("\"a\" + \"b\"", "ab"),
+        ("2 * 21", "42"),
+        ("[1, 2]", "[1, 2]"),
+        ("{\"k\": 1}", "{\"k\": 1}"),
+        ("-7", "-7"),
+        ("!cap", "ong_no_cap"),
+        ("walashi", "walashi"),
+        ("\"{1 + 1}\"", "2"),
+    ];
+    for (source, expected) in cases {
+        for backend in [Backend::TreeWalker, Backend::Vm] {
+            let (_, _, result) = run(source, backend);
+            assert_eq!(result, Ok(expected.to_string()), "{}", source);
+        }
+    }
+}
+
+#[test]
+fn statements_end_at_the_end_of_the_line() {
+    let cases = [
+        ("3mol highkey \"a\" \"b\"", "line 1, column 18"),
+        ("hayde x hiyye 1 2", "line 1, column 17"),
+        ("hayde xs hiyye [1]\nxs[0] hiyye 1 xs", "line 2, column 15"),
+    ];
+    for (source, position) in cases {
+        for backend in [Backend::TreeWalker, Backend::Vm] {
+            let (output, _, result) = run(source, backend);
+            let err = result.unwrap_err();
+            assert!(err.contains(position) && err.contains("expected end of line"), "{}", err);
+            assert_eq!(output, "");
+        }
+    }
+    // A line starting with '-' is a statement of its own, not a subtraction
+    let source = "hayde a hiyye 5\n-3\n3mol highkey a\neza ong_no_cap lakan 3mol highkey [1,\n2] deal";
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let (output, _, result) = run(source, backend);
+        assert_eq!(result, Ok("walashi".to_string()));
+        assert_eq!(output, "5\n[1, 2]\n");
+    }
+}
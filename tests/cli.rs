@@ -0,0 +1,44 @@
+// Runs the faysallang binary the way shell scripts and CI do
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use common::scratch_dir;
+
+fn run_script(dir: &Path, source: &str, args: &[&str]) -> Output {
+    let script = dir.join("script.fsl");
+    fs::write(&script, source).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_faysallang"))
+        .arg(&script)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn exit_codes() {
+    let dir = scratch_dir("exit-codes");
+    let cases = [
+        ("3mol highkey 1", 0),
+        ("3mol highkey 1\nexit(3)\n3mol highkey 2", 3),
+        ("exit()", 0),
+        ("hayde x hiyye (", 65),
+        ("3mol highkey \"\\q\"", 65),
+        ("3mol highkey nope", 65),
+        ("// only a comment", 65),
+        ("", 65),
+        (" \n\n", 65),
+        ("3mol highkey 1 / 0", 70),
+        ("exit(256)", 70),
+    ];
+    for (source, expected) in cases {
+        for backend in [&[][..], &["--tree-walker"][..]] {
+            let output = run_script(&dir, source, backend);
+            assert_eq!(output.status.code(), Some(expected), "{:?} {:?}", source, backend);
+        }
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
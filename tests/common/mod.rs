@@ -0,0 +1,11 @@
+// Helpers shared by the integration tests
+use std::fs;
+use std::path::PathBuf;
+
+// A fresh, empty directory for one test's files
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("faysallang-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
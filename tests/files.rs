@@ -0,0 +1,118 @@
+// The file built-ins may only touch files inside the directories allowed with allow_fs
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use faysallang::{Interpreter, Value};
+
+// A scratch directory holding an allowed "box" directory and a file outside it
+fn sandbox_dir(name: &str) -> PathBuf {
+    let dir = common::scratch_dir(name);
+    fs::create_dir_all(dir.join("box/sub")).unwrap();
+    fs::write(dir.join("secret.txt"), "top secret").unwrap();
+    fs::write(dir.join("box/notes.txt"), "one\ntwo\n").unwrap();
+    dir
+}
+
+fn sandboxed(dir: &Path) -> Interpreter {
+    let mut interpreter = Interpreter::new();
+    interpreter.allow_fs(dir.join("box")).unwrap();
+    interpreter
+}
+
+// Runs a call on a path inside the scratch directory, returning its value or error message
+fn call(interpreter: &mut Interpreter, dir: &Path, call: &str, path: &str) -> Result<Value, String> {
+    let path = format!("{}/{}", dir.display(), path);
+    interpreter.set_global("path", path.as_str());
+    interpreter.eval(&format!("{}(path)", call)).map_err(|err| err.to_string())
+}
+
+fn assert_outside(result: Result<Value, String>) {
+    let err = result.unwrap_err();
+    assert!(err.contains("is outside the directories file access is allowed in"), "{}", err);
+}
+
+#[test]
+fn file_access_is_disabled_by_default() {
+    let dir = sandbox_dir("disabled");
+    let mut interpreter = Interpreter::new();
+    for function in ["read_file", "read_lines", "file_exists", "list_dir"] {
+        let err = call(&mut interpreter, &dir, function, "box/notes.txt").unwrap_err();
+        assert!(err.contains("file access is disabled"), "{}", err);
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn files_inside_the_sandbox_can_be_used() {
+    let dir = sandbox_dir("inside");
+    let mut interpreter = sandboxed(&dir);
+    assert_eq!(call(&mut interpreter, &dir, "read_file", "box/notes.txt"), Ok(Value::from("one\ntwo\n")));
+    assert_eq!(
+        call(&mut interpreter, &dir, "read_lines", "box/sub/../notes.txt").unwrap().to_string(),
+        "[\"one\", \"two\"]"
+    );
+    assert_eq!(call(&mut interpreter, &dir, "file_exists", "box/notes.txt"), Ok(Value::Bool(true)));
+    assert_eq!(call(&mut interpreter, &dir, "file_exists", "box/new.txt"), Ok(Value::Bool(false)));
+    
+    interpreter.eval("write_file(path + \".out\", [1, 2])\nappend_file(path + \".out\", \"!\")").unwrap();
+    assert_eq!(fs::read_to_string(dir.join("box/new.txt.out")).unwrap(), "[1, 2]!");
+    assert_eq!(
+        call(&mut interpreter, &dir, "list_dir", "box").unwrap().to_string(),
+        "[\"new.txt.out\", \"notes.txt\", \"sub\"]"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn missing_directories_inside_the_sandbox_dont_exist() {
+    let dir = sandbox_dir("missing");
+    let mut interpreter = sandboxed(&dir);
+    assert_eq!(call(&mut interpreter, &dir, "file_exists", "box/nope/x"), Ok(Value::Bool(false)));
+    assert_eq!(call(&mut interpreter, &dir, "file_exists", "box/nope/../notes.txt"), Ok(Value::Bool(false)));
+    let err = call(&mut interpreter, &dir, "read_file", "box/nope/x").unwrap_err();
+    assert!(err.contains("can't read"), "{}", err);
+    assert_outside(call(&mut interpreter, &dir, "file_exists", "box/nope/../../secret.txt"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn paths_leading_out_of_the_sandbox_are_refused() {
+    let dir = sandbox_dir("outside");
+    let mut interpreter = sandboxed(&dir);
+    for function in ["read_file", "file_exists"] {
+        assert_outside(call(&mut interpreter, &dir, function, "secret.txt"));
+        assert_outside(call(&mut interpreter, &dir, function, "box/../secret.txt"));
+        assert_outside(call(&mut interpreter, &dir, function, "box/sub/../../secret.txt"));
+    }
+    assert_outside(call(&mut interpreter, &dir, "list_dir", "box/.."));
+    interpreter.set_global("path", format!("{}/box/../escaped.txt", dir.display()));
+    assert_outside(interpreter.eval("write_file(path, 1)").map_err(|err| err.to_string()));
+    assert!(!dir.join("escaped.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_leading_out_of_the_sandbox_are_refused() {
+    use std::os::unix::fs::symlink;
+    
+    let dir = sandbox_dir("symlinks");
+    symlink(dir.join("secret.txt"), dir.join("box/link")).unwrap();
+    symlink(&dir, dir.join("box/dir_link")).unwrap();
+    symlink(dir.join("not_there.txt"), dir.join("box/dangling")).unwrap();
+    let mut interpreter = sandboxed(&dir);
+    
+    assert_outside(call(&mut interpreter, &dir, "read_file", "box/link"));
+    assert_outside(call(&mut interpreter, &dir, "read_file", "box/dir_link/secret.txt"));
+    assert_outside(call(&mut interpreter, &dir, "list_dir", "box/dir_link"));
+    // Writing through a dangling symlink would create a file wherever it points
+    interpreter.set_global("path", format!("{}/box/dangling", dir.display()));
+    for source in ["write_file(path, 1)", "file_exists(path)"] {
+        let err = interpreter.eval(source).unwrap_err().to_string();
+        assert!(err.contains("broken symlink"), "{}", err);
+    }
+    assert!(!dir.join("not_there.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
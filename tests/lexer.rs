@@ -0,0 +1,176 @@
+use faysallang::{BigInt, Error, StringPart, Token};
+
+fn lex_error(source: &str) -> (String, usize, usize) {
+    match faysallang::tokenize(source) {
+        Err(Error::Lex(err)) => (err.message, err.span.line, err.span.column),
+        other => panic!("expected a lex error for {:?}, got {:?}", source, other),
+    }
+}
+
+#[test]
+fn unexpected_characters_are_errors() {
+    assert_eq!(
+        lex_error("hayde y hiyye a | b"),
+        ("Unexpected character '|'; use '||' for 'or'".to_string(), 1, 17)
+    );
+    assert_eq!(
+        lex_error("3mol highkey a & b"),
+        ("Unexpected character '&'; use '&&' for 'and'".to_string(), 1, 16)
+    );
+    assert_eq!(lex_error("3mol highkey y;"), ("Unexpected character ';'".to_string(), 1, 15));
+    assert_eq!(lex_error("\n3mol $b"), ("Unexpected character '$'".to_string(), 2, 6));
+    assert_eq!(lex_error("\"{1 @ 2}\""), ("Unexpected character '@'".to_string(), 1, 5));
+}
+
+#[test]
+fn whitespace_and_comments_are_skipped() {
+    let tokens = faysallang::tokenize("\u{feff}3mol\thighkey\u{a0}1 // $ | ;\r\n").unwrap();
+    let tokens: Vec<_> = tokens.into_iter().map(|token| token.token).collect();
+    assert_eq!(tokens, [Token::ThreeMol, Token::Highkey, Token::Int(1), Token::Newline, Token::Eof]);
+}
+
+#[test]
+fn newlines_inside_brackets_are_skipped() {
+    let tokens = faysallang::tokenize("f(1,\n[2,\n{3: 4}])\nx").unwrap();
+    let newlines = tokens.iter().filter(|token| token.token == Token::Newline).count();
+    assert_eq!(newlines, 1);
+}
+
+// The only token `source` holds
+fn single_token(source: &str) -> Token {
+    let tokens = faysallang::tokenize(source).unwrap();
+    match &tokens[..] {
+        [token, eof] if eof.token == Token::Eof => token.token.clone(),
+        other => panic!("expected one token for {:?}, got {:?}", source, other),
+    }
+}
+
+#[test]
+fn number_literals() {
+    let big = |s: &str| Token::BigInt(BigInt::parse(s).unwrap());
+    let cases = [
+        ("0", Token::Int(0)),
+        ("1_000_000", Token::Int(1_000_000)),
+        ("9223372036854775807", Token::Int(i64::MAX)),
+        ("9223372036854775808", big("9223372036854775808")),
+        ("0xff", Token::Int(255)),
+        ("0XFF", Token::Int(255)),
+        ("0xdead_beef", Token::Int(0xdead_beef)),
+        ("0x1_0000_0000_0000_0000", big("18446744073709551616")),
+        ("0b1010", Token::Int(10)),
+        ("0b1111_0000", Token::Int(240)),
+        ("0o755", Token::Int(493)),
+        ("0o7_7", Token::Int(63)),
+        ("1.5", Token::Float(1.5)),
+        ("1_000.000_5", Token::Float(1000.0005)),
+        ("1e6", Token::Float(1e6)),
+        ("1E6", Token::Float(1e6)),
+        ("2.5e-3", Token::Float(2.5e-3)),
+        ("2.5e+3", Token::Float(2500.0)),
+        ("1e1_0", Token::Float(1e10)),
+        ("1e308", Token::Float(1e308)),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(single_token(source), expected, "{}", source);
+    }
+}
+
+#[test]
+fn malformed_number_literals() {
+    let cases = [
+        ("1__0", "'_' must be between two digits in number literal '1__0'"),
+        ("1_", "'_' must be between two digits in number literal '1_'"),
+        ("1_.5", "'_' must be between two digits in number literal '1_.5'"),
+        ("1._5", "'_' must be between two digits in number literal '1._5'"),
+        ("0x_", "'_' must be between two digits in number literal '0x_'"),
+        ("0x_1", "'_' must be between two digits in number literal '0x_1'"),
+        ("0x", "Number literal '0x' has no digits after its prefix"),
+        ("0b102", "Invalid digit '2' in binary literal '0b102'"),
+        ("0o8", "Invalid digit '8' in octal literal '0o8'"),
+        ("0xfg", "Invalid digit 'g' in hexadecimal literal '0xfg'"),
+        ("1.2.3", "Malformed number literal '1.2.3'"),
+        ("1e", "Malformed number literal '1e'"),
+        ("1.", "Malformed number literal '1.'"),
+        ("1e309", "Number literal '1e309' is too large"),
+    ];
+    for (source, message) in cases {
+        assert_eq!(lex_error(source), (message.to_string(), 1, 1), "{}", source);
+    }
+}
+
+#[test]
+fn string_escapes() {
+    let cases = [
+        (r#""a\nb""#, "a\nb"),
+        (r#""\t\r\0""#, "\t\r\0"),
+        (r#""\\ \" \'""#, "\\ \" '"),
+        (r#""\{not code\}""#, "{not code}"),
+        (r#""\u{e9}\u{1F600}\u{0}""#, "\u{e9}\u{1F600}\u{0}"),
+        (r#""\u{10FFFF}""#, "\u{10FFFF}"),
+        (r#""\u{00000a}""#, "\n"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(single_token(source), Token::String(expected.to_string()), "{}", source);
+    }
+}
+
+#[test]
+fn invalid_string_escapes() {
+    let cases = [
+        (r#""\q""#, "Unknown escape sequence '\\q' in string", 2),
+        ("\"a\\\nb\"", "Unknown escape sequence: '\\' at the end of a line in string", 3),
+        (r#""\u{110000}""#, "Invalid unicode escape '\\u{110000}' in string", 2),
+        (r#""\u{D800}""#, "Invalid unicode escape '\\u{D800}' in string", 2),
+        (r#""\u{DFFF}""#, "Invalid unicode escape '\\u{DFFF}' in string", 2),
+        (r#""\u{}""#, "Invalid unicode escape '\\u{}' in string", 2),
+        (r#""\u{1234567}""#, "Invalid unicode escape '\\u{1234567}' in string", 2),
+        (r#""\u{zz}""#, "Invalid unicode escape '\\u{zz}' in string", 2),
+        (r#""\u{e9""#, "Unicode escape '\\u{e9' is missing its closing '}'", 2),
+        (r#""\u00e9""#, "Unicode escapes are written '\\u{...}', e.g. '\\u{e9}'", 2),
+        ("\"abc\\", "Unterminated string: missing closing '\"'", 5),
+    ];
+    for (source, message, column) in cases {
+        assert_eq!(lex_error(source), (message.to_string(), 1, column), "{}", source);
+    }
+}
+
+#[test]
+fn string_interpolation() {
+    let code = |source: &str| match single_token(source) {
+        Token::InterpolatedString(parts) => parts,
+        other => panic!("expected an interpolated string for {}, got {:?}", source, other),
+    };
+    let tokens = |part: &StringPart| match part {
+        StringPart::Code(tokens) => tokens.iter().map(|token| token.token.clone()).collect::<Vec<_>>(),
+        StringPart::Literal(text) => panic!("expected code, got {:?}", text),
+    };
+    
+    let parts = code(r#""a{x}b""#);
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[0], StringPart::Literal("a".to_string()));
+    assert_eq!(tokens(&parts[1]), [Token::Identifier("x".to_string()), Token::RightBrace]);
+    assert_eq!(parts[2], StringPart::Literal("b".to_string()));
+    
+    // A map literal's braces nest inside the interpolation
+    let parts = code(r#""{ {1: 2}[1] }""#);
+    assert_eq!(tokens(&parts[0]).len(), 9);
+    
+    // So does a string with its own interpolation
+    let parts = code(r#""{"in{1}" + "}"}""#);
+    let inner = tokens(&parts[0]);
+    assert!(matches!(inner[0], Token::InterpolatedString(_)), "{:?}", inner);
+    assert_eq!(inner[1..], [Token::Plus, Token::String("}".to_string()), Token::RightBrace]);
+    
+    let unclosed = "Unterminated '{' in string: missing closing '}' (write '\\{' for a literal brace)";
+    let cases = [
+        (r#""{x"#, unclosed, 2),
+        (r#""{ {1: 2}"#, unclosed, 2),
+        ("\"{x\n}\"", unclosed, 2),
+        (r#""a{"b{1"#, unclosed, 6),
+        // The quote inside the braces starts another string
+        (r#""{x""#, "Unterminated string: missing closing '\"'", 4),
+    ];
+    for (source, message, column) in cases {
+        assert_eq!(lex_error(source), (message.to_string(), 1, column), "{}", source);
+    }
+}
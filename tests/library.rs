@@ -0,0 +1,163 @@
+use faysallang::{Backend, Interpreter};
+
+// What `source` evaluates to, printed, or its error message; both backends must agree
+fn eval(source: &str) -> Result<String, String> {
+    let results: Vec<_> = [Backend::TreeWalker, Backend::Vm]
+        .into_iter()
+        .map(|backend| {
+            let mut interpreter = Interpreter::with_backend(backend);
+            interpreter.eval(source).map(|value| value.to_string()).map_err(|err| err.to_string())
+        })
+        .collect();
+    assert_eq!(results[0], results[1], "backends differ on {}", source);
+    results[0].clone()
+}
+
+fn check(cases: &[(&str, &str)]) {
+    for (source, expected) in cases {
+        assert_eq!(eval(source), Ok(expected.to_string()), "{}", source);
+    }
+}
+
+// Each source must fail with an error containing the message
+fn check_errors(cases: &[(&str, &str)]) {
+    for (source, message) in cases {
+        let err = eval(source).unwrap_err();
+        assert!(err.contains(message), "{}: {}", source, err);
+    }
+}
+
+#[test]
+fn slice_counts_characters() {
+    check(&[
+        ("slice(\"marhaba\", 1, 4)", "arh"),
+        ("slice(\"marhaba\", 3)", "haba"),
+        ("slice(\"héllo wörld\", 1, 8)", "éllo wö"),
+        ("slice(\"🙂👋🏽x\", 1)", "👋🏽x"),
+        ("slice(\"abc\", 3)", ""),
+        ("slice(\"abc\", 1, 1)", ""),
+        ("slice([1, 2, 3], 1)", "[2, 3]"),
+        ("slice([1, 2, 3], 0, 0)", "[]"),
+    ]);
+    check_errors(&[
+        ("slice(\"héllo\", 2, 6)", "slice(): range 2 to 6 is out of bounds for a string of length 5"),
+        ("slice(\"héllo\", 6)", "slice(): range 6 to 5 is out of bounds for a string of length 5"),
+        ("slice(\"abc\", 2, 1)", "slice(): range 2 to 1 is out of bounds for a string of length 3"),
+        ("slice([1], 0, 2)", "slice(): range 0 to 2 is out of bounds for a list of length 1"),
+        ("slice(\"abc\", -1)", "slice(): position must not be negative, got -1"),
+        ("slice(\"abc\", pow(2, 70))", "slice(): integer argument doesn't fit in 64 bits"),
+        ("slice(5, 1)", "slice() expected a string or list, found int"),
+        ("slice(\"abc\")", "slice() takes 3 arguments, got 1"),
+    ]);
+}
+
+#[test]
+fn split_and_the_other_string_functions() {
+    check(&[
+        ("split(\"a,b,,c\", \",\")", "[\"a\", \"b\", \"\", \"c\"]"),
+        ("split(\"é🙂e\", \"\")", "[\"é\", \"🙂\", \"e\"]"),
+        ("split(\"x→y→z\", \"→\")", "[\"x\", \"y\", \"z\"]"),
+        ("split(\"\", \",\")", "[\"\"]"),
+        ("split(\"\", \"\")", "[]"),
+        ("join(split(\"a b c\", \" \"), \"-\")", "a-b-c"),
+        ("join([1, \"x\", walashi], \", \")", "1, x, walashi"),
+        ("find(\"héllo\", \"l\")", "2"),
+        ("find(\"héllo\", \"z\")", "walashi"),
+        ("contains(\"héllo\", \"él\")", "ong_no_cap"),
+        ("replace(\"a-b-c\", \"-\", \"+\")", "a+b+c"),
+        ("upper(\"straße\")", "STRASSE"),
+        ("lower(\"ÉCOLE\")", "école"),
+        ("trim(\"  hi \\n\")", "hi"),
+        ("number(\" 42 \") + number(\"2.5\")", "44.5"),
+        ("number(\"99999999999999999999\")", "99999999999999999999"),
+        ("number(\"nan\")", "walashi"),
+    ]);
+    check_errors(&[
+        ("split(\"abc\")", "split() takes 2 arguments, got 1"),
+        ("split(\"abc\", 1)", "split() expected a string, found int"),
+        ("replace(\"abc\", \"\", \"x\")", "replace(): the text to replace can't be empty"),
+    ]);
+}
+
+#[test]
+fn math_results() {
+    check(&[
+        ("abs(-5)", "5"),
+        ("abs(-9223372036854775807 - 1)", "9223372036854775808"),
+        ("abs(-2.5)", "2.5"),
+        ("sqrt(16)", "4.0"),
+        ("sqrt(2)", "1.4142135623730951"),
+        ("pow(2, 10)", "1024"),
+        ("pow(-3, 3)", "-27"),
+        ("pow(2, 64)", "18446744073709551616"),
+        ("pow(7, 0)", "1"),
+        ("pow(2, -1)", "0.5"),
+        ("pow(4, 0.5)", "2.0"),
+        ("floor(2.7)", "2"),
+        ("floor(-2.5)", "-3"),
+        ("ceil(2.1)", "3"),
+        ("round(2.5)", "3"),
+        ("round(-2.5)", "-3"),
+        ("floor(7)", "7"),
+        ("floor(1e20)", "100000000000000000000"),
+        ("min(3, 1, 2)", "1"),
+        ("max([3, 9.5, 2])", "9.5"),
+        ("min(2, 2.0)", "2"),
+        ("sin(0)", "0.0"),
+        ("cos(0)", "1.0"),
+        ("tan(0)", "0.0"),
+        ("exp(0)", "1.0"),
+        ("log(E)", "1.0"),
+        ("log(8, 2)", "3.0"),
+        ("log(1000, 10)", "3.0"),
+        ("log(81, 3)", "4.0"),
+        ("PI", "3.141592653589793"),
+    ]);
+    check_errors(&[
+        ("sqrt(-1)", "sqrt(): can't take the square root of a negative number (-1)"),
+        ("pow(-8, 0.5)", "pow(): -8 to the power of 0.5 is not a real number"),
+        ("pow(10, 1000000)", "pow(): result is too large to compute"),
+        ("log(0)", "log(): logarithm is only defined for positive numbers, got 0"),
+        ("log(8, 1)", "log(): logarithm base must be positive and not 1, got 1"),
+        ("floor(1e308 * 10)", "floor(): inf can't be rounded to an integer"),
+        ("min()", "min(): needs at least one number"),
+        ("max(1, \"2\")", "max() expected numbers, found string"),
+        ("abs(\"1\")", "abs() expected a number, found string"),
+    ]);
+}
+
+#[test]
+fn the_same_seed_gives_the_same_numbers() {
+    let source = "[random_int(1, 100), random_int(1, 100), random_int(1, 100), random(), choice([\"a\", \"b\"])]";
+    // SplitMix64 from seed 42, so the sequence is the same on every platform and release
+    let expected = "[14, 92, 59, 0.34419071652363753, \"a\"]";
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let mut interpreter = Interpreter::with_backend(backend);
+        interpreter.set_seed(42);
+        assert_eq!(interpreter.eval(source).unwrap().to_string(), expected);
+        // Seeding again starts the sequence over
+        interpreter.set_seed(42);
+        assert_eq!(interpreter.eval(source).unwrap().to_string(), expected);
+        interpreter.set_seed(43);
+        assert_ne!(interpreter.eval(source).unwrap().to_string(), expected);
+    }
+    
+    let mut interpreter = Interpreter::new();
+    interpreter.set_seed(7);
+    let source = "hayde ok hiyye ong_no_cap\n\
+                  hayde i hiyye 0\n\
+                  khalas betshil i < 1000 lakan\n\
+                  \x20  hayde n hiyye random_int(-2, 2)\n\
+                  \x20  hayde f hiyye random()\n\
+                  \x20  ok hiyye ok && n >= -2 && n <= 2 && f >= 0 && f < 1\n\
+                  \x20  i hiyye i + 1\n\
+                  deal\n\
+                  ok";
+    assert_eq!(interpreter.eval(source).unwrap().to_string(), "ong_no_cap");
+    check(&[("random_int(5, 5)", "5"), ("choice([7])", "7")]);
+    check_errors(&[
+        ("random_int(2, 1)", "random_int(): min (2) is larger than max (1)"),
+        ("choice([])", "choice(): can't choose from an empty list"),
+        ("random(1)", "random() takes 0 arguments, got 1"),
+    ]);
+}
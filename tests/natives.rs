@@ -0,0 +1,61 @@
+use faysallang::{Backend, Error, Interpreter, RuntimeError, RuntimeErrorKind, Value};
+
+fn interpreter(backend: Backend) -> Interpreter {
+    let mut interpreter = Interpreter::with_backend(backend);
+    interpreter.register_native("add", |args| {
+        args.expect_arity(2)?;
+        Ok(Value::Int(args.int(0)? + args.int(1)?))
+    });
+    interpreter.register_native("fail", |args| Err(args.error("went wrong")));
+    interpreter.register_native("len", |_| Ok(Value::from("native")));
+    interpreter
+}
+
+fn runtime_error(backend: Backend, source: &str) -> RuntimeError {
+    match interpreter(backend).eval(source) {
+        Err(Error::Runtime(err)) => err,
+        other => panic!("expected a runtime error for {:?}, got {:?}", source, other),
+    }
+}
+
+#[test]
+fn natives_are_called_with_the_script_arguments() {
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let mut interpreter = interpreter(backend);
+        assert_eq!(interpreter.eval("add(2, add(3, 4))").unwrap(), Value::Int(9));
+        // A native shadows the built-in of the same name, and a 'dala' shadows the native
+        assert_eq!(interpreter.eval("len([1, 2])").unwrap(), Value::from("native"));
+        let source = "dala add(a, b) lakan\n   rajje3 a - b\ndeal\nadd(5, 1)";
+        assert_eq!(interpreter.eval(source).unwrap(), Value::Int(4));
+    }
+}
+
+#[test]
+fn natives_report_bad_calls_at_the_call_site() {
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let err = runtime_error(backend, "3mol highkey 1\n3mol highkey add(1)");
+        let expected = RuntimeErrorKind::ArityMismatch { function: "add".to_string(), expected: 2, found: 1 };
+        assert_eq!(err.kind, expected);
+        assert_eq!(err.span.map(|span| (span.line, span.column)), Some((2, 14)));
+        
+        let err = runtime_error(backend, "add(1, \"2\")");
+        let expected = RuntimeErrorKind::ArgumentType {
+            function: "add".to_string(),
+            expected: "an integer",
+            found: "string",
+        };
+        assert_eq!(err.kind, expected);
+    }
+}
+
+#[test]
+fn native_errors_carry_the_script_stack() {
+    let source = "dala outer() lakan\n   rajje3 inner()\ndeal\ndala inner() lakan\n   rajje3 fail()\ndeal\nouter()";
+    for backend in [Backend::TreeWalker, Backend::Vm] {
+        let err = runtime_error(backend, source);
+        assert_eq!(err.kind, RuntimeErrorKind::Custom("fail(): went wrong".to_string()));
+        assert_eq!(err.span.map(|span| (span.line, span.column)), Some((5, 11)));
+        let functions: Vec<_> = err.stack.iter().map(|frame| frame.function.as_str()).collect();
+        assert_eq!(functions, ["inner", "outer"]);
+    }
+}
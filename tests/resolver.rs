@@ -0,0 +1,51 @@
+use faysallang::{Error, Interpreter, ResolveErrorKind, WarningKind};
+
+// Every error the resolver finds in `source`, with its line and column
+fn resolve_errors(source: &str) -> Vec<(ResolveErrorKind, usize, usize)> {
+    let program = faysallang::parse(source).unwrap();
+    match Interpreter::new().check(&program) {
+        Err(Error::Resolve(errors)) => {
+            errors.into_iter().map(|err| (err.kind, err.span.line, err.span.column)).collect()
+        }
+        other => panic!("expected resolve errors for {:?}, got {:?}", source, other),
+    }
+}
+
+fn warnings(source: &str) -> Vec<(WarningKind, usize, usize)> {
+    let program = faysallang::parse(source).unwrap();
+    let warnings = Interpreter::new().check(&program).unwrap();
+    warnings.into_iter().map(|warning| (warning.kind, warning.span.line, warning.span.column)).collect()
+}
+
+#[test]
+fn errors_point_at_the_mistake() {
+    assert_eq!(
+        resolve_errors("hayde count hiyye 1\n3mol highkey cont + count"),
+        [(ResolveErrorKind::UndefinedVariable("cont".to_string()), 2, 14)]
+    );
+    assert_eq!(
+        resolve_errors("dala f() lakan\n   3mol highkey x\n   hayde x hiyye 1\ndeal"),
+        [(ResolveErrorKind::UsedBeforeDeclaration("x".to_string()), 2, 17)]
+    );
+    assert_eq!(
+        resolve_errors("hayde total hiyye 0\n3mol highkey total\neza ong_no_cap lakan\n   totl hiyye 1\ndeal"),
+        [(ResolveErrorKind::UndeclaredAssignment("totl".to_string()), 4, 4)]
+    );
+}
+
+#[test]
+fn warnings_point_at_the_declaration() {
+    assert_eq!(
+        warnings("dala f() lakan\n   hayde unused hiyye 1\ndeal\nf()"),
+        [(WarningKind::UnusedVariable("unused".to_string()), 2, 4)]
+    );
+    // Each parameter is reported where it's named, not at the 'dala'
+    assert_eq!(
+        warnings("dala add(a, b, c) lakan\n   rajje3 a + c\ndeal\n3mol highkey add(1, 2, 3)"),
+        [(WarningKind::UnusedParameter { function: "add".to_string(), name: "b".to_string() }, 1, 13)]
+    );
+    assert_eq!(
+        warnings("hayde x hiyye 1\n3mol highkey x\n  hayde x hiyye 2\n3mol highkey x"),
+        [(WarningKind::Redeclaration("x".to_string()), 3, 3)]
+    );
+}
@@ -0,0 +1,71 @@
+use faysallang::{BigInt, Interpreter, OutputBuffer, Value};
+
+// A host can build a BigInt holding a small number; it must act like the equal Int
+#[test]
+fn small_big_ints_behave_like_ints() {
+    let zero = Value::BigInt(BigInt::from_i64(0));
+    let five = Value::BigInt(BigInt::from_i64(5));
+    assert!(!zero.is_truthy());
+    assert!(five.is_truthy());
+    assert_eq!(five, Value::Int(5));
+    assert_eq!(Value::Int(5), five);
+    assert_ne!(five, Value::Int(6));
+    assert_eq!(five, Value::Float(5.0));
+    assert_eq!(five.as_int(), Some(5));
+    
+    let mut interpreter = Interpreter::new();
+    interpreter.set_global("zero", zero);
+    interpreter.set_global("five", five);
+    let output = OutputBuffer::new();
+    interpreter.set_output(output.clone());
+    interpreter.eval(
+        "hayde xs hiyye [10, 20, 30, 40, 50, 60]\n\
+         3mol highkey [five == 5, !zero, xs[five], slice(\"marhaba\", five), random_int(five, 5), pow(2, five)]",
+    )
+    .unwrap();
+    assert_eq!(output.take(), "[ong_no_cap, ong_no_cap, 60, \"ba\", 5, 32]\n");
+}
+
+// Rounding 2^53 + 1 to a float would make it equal to 2^53
+#[test]
+fn integers_and_floats_compare_exactly() {
+    let cases = [
+        ("9007199254740993 < 9007199254740992.0", "cap"),
+        ("9007199254740993 > 9007199254740992.0", "ong_no_cap"),
+        ("9007199254740993 == 9007199254740992.0", "cap"),
+        ("9007199254740992.0 < 9007199254740993", "ong_no_cap"),
+        ("9007199254740992 >= 9007199254740992.0", "ong_no_cap"),
+        ("-9007199254740993 < -9007199254740992.0", "ong_no_cap"),
+        ("2 < 2.5 && 3 > 2.5 && -3 < -2.5", "ong_no_cap"),
+        ("9223372036854775807 < 9223372036854775808.0", "ong_no_cap"),
+        ("18446744073709551617 > 18446744073709551616.0", "ong_no_cap"),
+        ("18446744073709551617 == 18446744073709551616.0", "cap"),
+        ("18446744073709551616 == 18446744073709551616.0", "ong_no_cap"),
+        ("pow(10, 400) < 1e308 * 10", "ong_no_cap"),
+        ("-pow(10, 400) > -1e308 * 10", "ong_no_cap"),
+        ("1 < 1e308 * 10 - 1e308 * 10 || 1 >= 1e308 * 10 - 1e308 * 10", "cap"),
+        ("max(9007199254740992.0, 9007199254740993)", "9007199254740993"),
+        ("min(9007199254740993, 9007199254740992.0)", "9007199254740992.0"),
+    ];
+    for (source, expected) in cases {
+        let mut interpreter = Interpreter::new();
+        let value = interpreter.eval(source).unwrap();
+        assert_eq!(value.to_string(), expected, "{}", source);
+    }
+    assert_eq!(BigInt::from_f64(-1e20).map(|n| n.to_string()), Some("-100000000000000000000".to_string()));
+    assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+}
+
+#[test]
+fn inexact_big_integer_division_gives_the_nearest_float() {
+    let cases = [
+        ("(pow(10, 400) + 1) / (2 * pow(10, 400))", "0.5"),
+        ("-pow(10, 30) / 7", "-1.4285714285714285e29"),
+        ("(pow(2, 64) + 1) / -pow(2, 62)", "-4.0"),
+        ("pow(10, 400) / 3", "inf"),
+    ];
+    for (source, expected) in cases {
+        let value = Interpreter::new().eval(source).unwrap();
+        assert_eq!(value.to_string(), expected, "{}", source);
+    }
+}